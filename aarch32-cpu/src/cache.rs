@@ -1,6 +1,213 @@
 use arbitrary_int::u3;
 
-use crate::register::{Dccimvac, Dccisw, Dccmvac, Dccsw, Dcimvac, Dcisw, SysRegWrite};
+use crate::register::{
+    clidr::CacheKind, Ccsidr, Clidr, Dccimvac, Dccisw, Dccmvac, Dccsw, Dcimvac, Dcisw, SysRegWrite,
+};
+
+/// The size and shape of one cache, from CCSIDR
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheGeometry {
+    /// log2 of the line length in bytes
+    pub log2_line_size: u32,
+    /// Number of ways
+    pub ways: u32,
+    /// Number of sets
+    pub sets: u32,
+}
+
+impl CacheGeometry {
+    /// Decode a CCSIDR value
+    pub const fn from_ccsidr(ccsidr: Ccsidr) -> CacheGeometry {
+        CacheGeometry {
+            log2_line_size: ccsidr.line_size().value() as u32 + 4,
+            ways: ccsidr.associativity().value() as u32 + 1,
+            sets: ccsidr.num_sets().value() as u32 + 1,
+        }
+    }
+
+    /// Line length in bytes
+    pub const fn line_size(&self) -> u32 {
+        1 << self.log2_line_size
+    }
+
+    /// Total size in bytes
+    pub const fn size(&self) -> u32 {
+        self.line_size() * self.ways * self.sets
+    }
+
+    /// The value to write to DCISW, DCCSW or DCCISW for one line of this cache
+    pub const fn set_way(&self, level: u3, way: u32, set: u32) -> u32 {
+        // log2(ways), rounded up
+        let a = u32::BITS - (self.ways - 1).leading_zeros();
+        let way = if a == 0 { 0 } else { way << (32 - a) };
+        way | (set << self.log2_line_size) | ((level.value() as u32) << 1)
+    }
+}
+
+/// One level of the cache hierarchy, from CLIDR
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheLevel {
+    /// The level, counting from zero for L1
+    pub level: u3,
+    /// The caches at this level
+    pub kind: CacheKind,
+}
+
+/// An iterator over the cache levels described by a CLIDR value
+///
+/// Stops at the first level without a cache.
+#[derive(Debug, Clone)]
+pub struct CacheLevels {
+    clidr: Clidr,
+    next: u8,
+}
+
+impl CacheLevels {
+    /// Iterate over the levels in `clidr`
+    pub const fn new(clidr: Clidr) -> CacheLevels {
+        CacheLevels { clidr, next: 0 }
+    }
+}
+
+impl Iterator for CacheLevels {
+    type Item = CacheLevel;
+
+    fn next(&mut self) -> Option<CacheLevel> {
+        // CLIDR describes at most seven levels
+        if self.next >= 7 {
+            return None;
+        }
+        let level = u3::new(self.next);
+        let kind = self.clidr.cache_kind(level);
+        if kind == CacheKind::None {
+            self.next = u8::MAX;
+            return None;
+        }
+        self.next += 1;
+        Some(CacheLevel { level, kind })
+    }
+}
+
+/// Iterate over the cache levels this processor implements
+#[inline]
+pub fn levels() -> CacheLevels {
+    CacheLevels::new(Clidr::read())
+}
+
+/// Read the geometry of the data or unified cache at `level`
+///
+/// This writes CSSELR, so it must not race with anything else using it.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn geometry(level: u3) -> CacheGeometry {
+    use crate::register::{csselr::CacheType, Csselr};
+    unsafe {
+        Csselr::write(
+            Csselr::new_with_raw_value(0)
+                .with_level(level)
+                .with_cache_type(CacheType::DataOrUnified),
+        )
+    };
+    crate::asm::isb();
+    CacheGeometry::from_ccsidr(Ccsidr::read())
+}
+
+/// Call `op` with the set/way value of every line in every data or unified
+/// cache below the Level of Coherency
+#[cfg(any(
+    test,
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+fn for_each_line_to_loc(
+    clidr: Clidr,
+    mut geometry: impl FnMut(u3) -> CacheGeometry,
+    mut op: impl FnMut(u32),
+) {
+    let loc = clidr.loc().value();
+    for level in CacheLevels::new(clidr) {
+        if level.level.value() >= loc || !level.kind.has_data() {
+            continue;
+        }
+        let geometry = geometry(level.level);
+        for way in 0..geometry.ways {
+            for set in 0..geometry.sets {
+                op(geometry.set_way(level.level, way, set));
+            }
+        }
+    }
+}
+
+/// Run a set/way operation over every data or unified cache up to the Level of Coherency
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+fn set_way_to_loc(op: impl FnMut(u32)) {
+    crate::asm::dmb();
+    for_each_line_to_loc(Clidr::read(), geometry, op);
+    unsafe {
+        crate::register::Csselr::write(crate::register::Csselr::new_with_raw_value(0));
+    }
+    crate::asm::dsb();
+    crate::asm::isb();
+}
+
+/// Clean every data and unified cache, up to the Level of Coherency
+///
+/// The cache geometry is read from CLIDR and CCSIDR, so unlike
+/// [`clean_l1_data_cache`] this needs no parameters.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn clean_all() {
+    set_way_to_loc(|value| unsafe { Dccsw::write(Dccsw(value)) });
+}
+
+/// Invalidate every data and unified cache, up to the Level of Coherency
+///
+/// Dirty lines are discarded, not written back.
+///
+/// # Safety
+///
+/// The caches must not hold dirty data that is still needed, including the
+/// caller's own stack and statics. Typically this is only called before the
+/// data cache is enabled.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub unsafe fn invalidate_all() {
+    set_way_to_loc(|value| unsafe { Dcisw::write(Dcisw(value)) });
+}
+
+/// Clean and invalidate every data and unified cache, up to the Level of Coherency
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn clean_and_invalidate_all() {
+    set_way_to_loc(|value| unsafe { Dccisw::write(Dccisw(value)) });
+}
 
 /// Invalidate the full L1 data cache.
 ///
@@ -106,3 +313,373 @@ pub fn clean_and_invalidate_data_cache_line_to_poc(addr: u32) {
         Dccimvac::write_raw(addr);
     }
 }
+
+/// Call `op` with the address of every line that `start..start + len` touches
+#[cfg(any(
+    test,
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+fn for_each_line(start: usize, len: usize, line_size: usize, mut op: impl FnMut(u32)) {
+    if len == 0 {
+        return;
+    }
+    // Work with the last byte, so a range ending at the top of memory doesn't overflow
+    let last = start + (len - 1);
+    let last_line = last & !(line_size - 1);
+    let mut line = start & !(line_size - 1);
+    loop {
+        op(line as u32);
+        if line == last_line {
+            break;
+        }
+        line += line_size;
+    }
+}
+
+/// The start and length of an address range
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+fn start_and_len(start: usize, end: usize) -> (usize, usize) {
+    debug_assert!(start <= end, "range ends before it starts");
+    (start, end.saturating_sub(start))
+}
+
+/// Clean the data cache for a range of memory, to the point of coherency
+///
+/// Use this before a device reads memory that the processor has written.
+/// Every line the range touches is cleaned, using the smallest line size
+/// from CTR, and the function ends with a DSB.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn clean_range(range: core::ops::Range<*const u8>) {
+    let (start, len) = start_and_len(range.start as usize, range.end as usize);
+    let line_size = crate::register::Ctr::read().dcache_line_size();
+    for_each_line(start, len, line_size, clean_data_cache_line_to_poc);
+    crate::asm::dsb();
+}
+
+/// Invalidate the data cache for a range of memory, to the point of coherency
+///
+/// Use this after a device has written to memory, before reading it. Every
+/// line the range touches is invalidated, and the function ends with a DSB.
+///
+/// Before starting the transfer, call [`clean_and_invalidate_range`] on the
+/// same range. That writes back anything dirty in the lines at either end
+/// which the range only partly covers, while the device is not yet writing.
+///
+/// [`DmaBuffer`] never shares a line with other data, and does all of this
+/// for you.
+///
+/// # Safety
+///
+/// Anything in the range written by the processor and not yet cleaned is
+/// discarded. So is anything written to the rest of a partly covered line at
+/// either end since it was last cleaned, which means the processor must not
+/// write to the memory next to the range while the device owns it.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub unsafe fn invalidate_range(range: core::ops::Range<*mut u8>) {
+    let (start, len) = start_and_len(range.start as usize, range.end as usize);
+    let line_size = crate::register::Ctr::read().dcache_line_size();
+    for_each_line(start, len, line_size, invalidate_data_cache_line_to_poc);
+    crate::asm::dsb();
+}
+
+/// Clean and invalidate the data cache for a range of memory, to the point of coherency
+///
+/// Ends with a DSB.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn clean_and_invalidate_range(range: core::ops::Range<*mut u8>) {
+    let (start, len) = start_and_len(range.start as usize, range.end as usize);
+    let line_size = crate::register::Ctr::read().dcache_line_size();
+    for_each_line(
+        start,
+        len,
+        line_size,
+        clean_and_invalidate_data_cache_line_to_poc,
+    );
+    crate::asm::dsb();
+}
+
+/// Make instructions written to memory visible to instruction fetches
+///
+/// Use this after copying or generating code in `range`, before running it.
+/// Cleans the data cache to the point of unification, then invalidates the
+/// instruction cache and branch predictor for the range, and ends with a DSB
+/// and an ISB.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub fn sync_icache_range(range: core::ops::Range<*const u8>) {
+    use crate::register::{BpIMva, Ctr, Dccmvau, Icimvau};
+
+    let (start, len) = start_and_len(range.start as usize, range.end as usize);
+    let ctr = Ctr::read();
+    for_each_line(start, len, ctr.dcache_line_size(), |line| unsafe {
+        Dccmvau::write(Dccmvau::new(line))
+    });
+    crate::asm::dsb();
+    for_each_line(start, len, ctr.icache_line_size(), |line| {
+        unsafe { Icimvau::write(Icimvau::new(line)) };
+        BpIMva::write(line);
+    });
+    crate::asm::dsb();
+    crate::asm::isb();
+}
+
+/// A value which is shared with a DMA-capable device
+///
+/// The value is aligned to [`DmaBuffer::ALIGN`] bytes and padded to a
+/// multiple of that, so it never shares a cache line with anything else and
+/// the maintenance on it cannot disturb neighbouring data.
+///
+/// Call [`prepare_for_device`](DmaBuffer::prepare_for_device) before starting
+/// a transfer, and [`complete_from_device`](DeviceOwned::complete_from_device)
+/// on the guard it returns once the transfer has finished.
+#[repr(C, align(64))]
+#[derive(Debug, Default)]
+pub struct DmaBuffer<T> {
+    value: T,
+}
+
+impl<T> DmaBuffer<T> {
+    /// The alignment of the buffer, which must be at least the cache line size
+    pub const ALIGN: usize = 64;
+
+    /// Wrap a value
+    pub const fn new(value: T) -> DmaBuffer<T> {
+        DmaBuffer { value }
+    }
+
+    /// Unwrap the value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// The addresses the buffer covers, including any padding
+    pub fn as_mut_ptr_range(&mut self) -> core::ops::Range<*mut u8> {
+        let start = core::ptr::addr_of_mut!(self.value) as *mut u8;
+        // Safety: the buffer is `size_of::<Self>()` bytes long
+        start..unsafe { start.add(core::mem::size_of::<Self>()) }
+    }
+
+    /// Hand the buffer to a device
+    ///
+    /// Cleans and invalidates the buffer, so a device reading it sees what
+    /// the processor wrote, and no dirty line is later evicted over what a
+    /// device writes.
+    ///
+    /// The returned guard borrows the buffer until the transfer is finished,
+    /// so the processor cannot touch it while the device owns it.
+    ///
+    /// # Panics
+    ///
+    /// If the data cache line size (from CTR) is larger than
+    /// [`DmaBuffer::ALIGN`], as maintenance on the buffer would then also
+    /// affect the data next to it.
+    #[cfg(any(
+        doc,
+        arm_architecture = "v7-a",
+        arm_architecture = "v7-r",
+        arm_architecture = "v8-r"
+    ))]
+    pub fn prepare_for_device(&mut self) -> DeviceOwned<'_, T> {
+        assert!(crate::register::Ctr::read().dcache_line_size() <= Self::ALIGN);
+        clean_and_invalidate_range(self.as_mut_ptr_range());
+        DeviceOwned { buffer: self }
+    }
+}
+
+/// A [`DmaBuffer`] which has been handed to a device
+///
+/// Dropping the guard takes the buffer back from the device, as
+/// [`complete_from_device`](DeviceOwned::complete_from_device) does.
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+#[must_use = "the buffer is taken back from the device when the guard is dropped"]
+pub struct DeviceOwned<'a, T> {
+    buffer: &'a mut DmaBuffer<T>,
+}
+
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+impl<'a, T> DeviceOwned<'a, T> {
+    /// The address to give the device
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        core::ptr::addr_of_mut!(self.buffer.value)
+    }
+
+    /// Take the buffer back from the device
+    ///
+    /// Call this once the transfer has finished. Invalidates the buffer,
+    /// discarding anything fetched speculatively while the device owned it,
+    /// so the processor sees what the device wrote.
+    pub fn complete_from_device(self) -> &'a mut T {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        this.invalidate();
+        // Safety: `this` is never dropped, so the reference is only moved out once
+        let buffer = unsafe { core::ptr::read(&this.buffer) };
+        &mut buffer.value
+    }
+
+    fn invalidate(&mut self) {
+        let range = self.buffer.as_mut_ptr_range();
+        // Safety: the buffer fills whole lines, so no other data is discarded
+        unsafe { invalidate_range(range) };
+    }
+}
+
+#[cfg(any(
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+impl<T> Drop for DeviceOwned<'_, T> {
+    fn drop(&mut self) {
+        self.invalidate();
+    }
+}
+
+impl<T> core::ops::Deref for DmaBuffer<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> core::ops::DerefMut for DmaBuffer<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::register::Ctr;
+    use std::vec::Vec;
+
+    /// L1 separate, L2 unified, L3 unified, with LoC = 2
+    const CLIDR: Clidr = Clidr((2 << 24) | (0b100 << 6) | (0b100 << 3) | 0b011);
+
+    #[test]
+    fn levels() {
+        let levels: Vec<_> = CacheLevels::new(CLIDR).map(|l| l.kind).collect();
+        assert_eq!(
+            levels,
+            [CacheKind::Separate, CacheKind::Unified, CacheKind::Unified]
+        );
+        assert_eq!(CacheLevels::new(Clidr(0)).count(), 0);
+        assert_eq!(CLIDR.loc(), u3::new(2));
+    }
+
+    #[test]
+    fn geometry() {
+        // 32 KiB, 4-way, 64-byte lines
+        let ccsidr = Ccsidr::new_with_raw_value((127 << 13) | (3 << 3) | 2);
+        let geometry = CacheGeometry::from_ccsidr(ccsidr);
+        assert_eq!(
+            geometry,
+            CacheGeometry {
+                log2_line_size: 6,
+                ways: 4,
+                sets: 128
+            }
+        );
+        assert_eq!(geometry.size(), 32 * 1024);
+        assert_eq!(
+            geometry.set_way(u3::new(1), 3, 127),
+            (3 << 30) | (127 << 6) | (1 << 1)
+        );
+        // The way field is sized by rounding up
+        let three_way = CacheGeometry {
+            ways: 3,
+            ..geometry
+        };
+        assert_eq!(three_way.set_way(u3::new(0), 2, 0), 2 << 30);
+        let direct = CacheGeometry {
+            ways: 1,
+            ..geometry
+        };
+        assert_eq!(direct.set_way(u3::new(0), 0, 1), 1 << 6);
+    }
+
+    #[test]
+    fn walk_to_loc() {
+        let small = CacheGeometry {
+            log2_line_size: 5,
+            ways: 2,
+            sets: 2,
+        };
+        let mut lines = Vec::new();
+        for_each_line_to_loc(CLIDR, |_| small, |v| lines.push(v));
+        // L3 is past the LoC
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], 0);
+        assert_eq!(lines[7], (1 << 31) | (1 << 5) | (1 << 1));
+    }
+
+    #[test]
+    fn range_lines() {
+        let mut lines = Vec::new();
+        for_each_line(0x1010, 0x40, 0x20, |l| lines.push(l));
+        assert_eq!(lines, [0x1000, 0x1020, 0x1040]);
+        lines.clear();
+        for_each_line(0x1000, 0x40, 0x20, |l| lines.push(l));
+        assert_eq!(lines, [0x1000, 0x1020]);
+        lines.clear();
+        for_each_line(0x1004, 4, 0x20, |l| lines.push(l));
+        assert_eq!(lines, [0x1000]);
+        lines.clear();
+        for_each_line(0x1004, 0, 0x20, |l| lines.push(l));
+        assert!(lines.is_empty());
+        lines.clear();
+        for_each_line(0xFFFF_FFE0, 0x20, 0x20, |l| lines.push(l));
+        assert_eq!(lines, [0xFFFF_FFE0]);
+    }
+
+    #[test]
+    fn dma_buffer_layout() {
+        assert_eq!(
+            core::mem::align_of::<DmaBuffer<u8>>(),
+            DmaBuffer::<u8>::ALIGN
+        );
+        assert_eq!(core::mem::size_of::<DmaBuffer<[u8; 65]>>(), 128);
+        assert_eq!(Ctr(0x8444_c004).dcache_line_size(), 64);
+    }
+}
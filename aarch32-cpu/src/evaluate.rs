@@ -0,0 +1,205 @@
+//! Works out what the MMU or MPU would do with a memory access
+//!
+//! This is plain Rust with no register accesses, so memory protection layouts
+//! can be checked in unit tests on the host before they ever reach a target.
+//! Each layout type implements [`Evaluate`]:
+//!
+//! * [`mmu::evaluate::Tables`](crate::mmu::evaluate::Tables), for a set of
+//!   Short-descriptor translation tables
+//! * [`pmsav7::Config`](crate::pmsav7::Config)
+//! * [`pmsav8::El1Config`](crate::pmsav8::El1Config) and
+//!   [`pmsav8::El2Config`](crate::pmsav8::El2Config)
+//!
+//! ```rust
+//! use aarch32_cpu::evaluate::{Access, Evaluate, Fault};
+//! use aarch32_cpu::pmsav7::{AccessPerms, Config, MemAttr, Region, RegionSize};
+//!
+//! let regions = [Region {
+//!     base: 0x2000_0000 as *mut u8,
+//!     size: RegionSize::_64K,
+//!     subregion_mask: 0,
+//!     enabled: true,
+//!     no_exec: true,
+//!     access: AccessPerms::PrivilegedOnly,
+//!     mem_attr: MemAttr::WriteBackWriteAllocate { shareable: false },
+//! }];
+//! let config = Config {
+//!     background_config: false,
+//!     iregions: &[],
+//!     dregions: &regions,
+//! };
+//! assert!(config.evaluate(0x2000_1000, Access::PRIVILEGED_WRITE).is_ok());
+//! assert_eq!(
+//!     config.evaluate(0x2000_1000, Access::USER_READ),
+//!     Err(Fault::MpuPermission)
+//! );
+//! assert_eq!(
+//!     config.evaluate(0x3000_0000, Access::PRIVILEGED_READ),
+//!     Err(Fault::Background)
+//! );
+//! ```
+
+/// What an access is trying to do
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccessType {
+    /// A data read
+    Read,
+    /// A data write
+    Write,
+    /// An instruction fetch
+    Execute,
+}
+
+/// A memory access to evaluate
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Access {
+    /// What the access is trying to do
+    pub kind: AccessType,
+    /// Is the access made at PL1 (or EL2, for an EL2 MPU)?
+    pub privileged: bool,
+}
+
+impl Access {
+    /// A read at PL1
+    pub const PRIVILEGED_READ: Access = Access::new(AccessType::Read, true);
+    /// A write at PL1
+    pub const PRIVILEGED_WRITE: Access = Access::new(AccessType::Write, true);
+    /// An instruction fetch at PL1
+    pub const PRIVILEGED_EXECUTE: Access = Access::new(AccessType::Execute, true);
+    /// A read at PL0
+    pub const USER_READ: Access = Access::new(AccessType::Read, false);
+    /// A write at PL0
+    pub const USER_WRITE: Access = Access::new(AccessType::Write, false);
+    /// An instruction fetch at PL0
+    pub const USER_EXECUTE: Access = Access::new(AccessType::Execute, false);
+
+    /// Describe an access
+    pub const fn new(kind: AccessType, privileged: bool) -> Access {
+        Access { kind, privileged }
+    }
+}
+
+/// What the accessing privilege level may do with some memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Permissions {
+    /// Data reads are allowed
+    pub read: bool,
+    /// Data writes are allowed
+    pub write: bool,
+    /// Instruction fetches are allowed
+    pub execute: bool,
+}
+
+impl Permissions {
+    /// Every kind of access is allowed
+    pub const ALL: Permissions = Permissions {
+        read: true,
+        write: true,
+        execute: true,
+    };
+
+    /// Build permissions from read and write access and the execute-never bit
+    ///
+    /// Instruction fetches need read access as well as a clear XN bit.
+    pub const fn new(read: bool, write: bool, execute_never: bool) -> Permissions {
+        Permissions {
+            read,
+            write,
+            execute: read && !execute_never,
+        }
+    }
+
+    /// Do these permissions allow `kind` of access?
+    pub const fn allows(&self, kind: AccessType) -> bool {
+        match kind {
+            AccessType::Read => self.read,
+            AccessType::Write => self.write,
+            AccessType::Execute => self.execute,
+        }
+    }
+}
+
+/// The result of an access which would not fault
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome<A> {
+    /// The physical address accessed (up to 40 bits, with Supersections)
+    pub physical_address: u64,
+    /// The memory attributes which apply to the access
+    pub attributes: A,
+    /// Everything the accessing privilege level may do at this address
+    pub permissions: Permissions,
+}
+
+/// Which MPU region an address falls in
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Matched<A> {
+    /// The address is in a region
+    Region {
+        /// The index of the region in the config
+        index: usize,
+        /// The attributes of the region
+        attributes: A,
+    },
+    /// No region matched, so the default memory map applies
+    Background,
+}
+
+/// The fault the hardware would raise for an access
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fault {
+    /// The translation table entry at this level is a fault entry
+    Translation {
+        /// Translation table level (1 or 2)
+        level: u8,
+    },
+    /// The domain of the entry is set to No Access in DACR
+    Domain {
+        /// Translation table level (1 or 2)
+        level: u8,
+    },
+    /// The access permissions of the entry do not allow the access
+    Permission {
+        /// Translation table level (1 or 2)
+        level: u8,
+    },
+    /// No MPU region matched, and the background region does not apply
+    Background,
+    /// The access permissions of the MPU region do not allow the access
+    MpuPermission,
+    /// The address is in more than one enabled PMSAv8 region
+    Overlap {
+        /// The lower numbered region
+        first: usize,
+        /// The higher numbered region
+        second: usize,
+    },
+    /// An L1 entry points at an L2 table which was not given to the evaluator
+    ///
+    /// This is a problem with the description of the tables, not a fault the
+    /// hardware would raise.
+    MissingL2Table {
+        /// The physical address of the L2 table
+        address: u32,
+    },
+}
+
+/// A memory protection layout which can be evaluated without hardware
+pub trait Evaluate {
+    /// The memory attributes reported for a successful access
+    type Attributes;
+
+    /// Work out what happens when `access` is made to virtual address `address`
+    fn evaluate(&self, address: u32, access: Access) -> Result<Outcome<Self::Attributes>, Fault>;
+}
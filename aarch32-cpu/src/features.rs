@@ -0,0 +1,295 @@
+//! Code for discovering which architectural features the current CPU implements
+//!
+//! The CPUID identification scheme (introduced in Armv6) describes the
+//! processor through a set of read-only ID registers, each holding a number
+//! of 4-bit fields. This module decodes the interesting ones into a single
+//! [`CpuFeatures`] structure.
+
+use crate::register::{IdDfr0, IdIsar0, IdIsar3, IdIsar4, IdMmfr0, IdPfr0, IdPfr1};
+
+/// Which hardware divide instructions are implemented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HardwareDivide {
+    /// No `SDIV`/`UDIV` instructions
+    None,
+    /// `SDIV`/`UDIV` in the Thumb instruction set only
+    ThumbOnly,
+    /// `SDIV`/`UDIV` in both the Arm and Thumb instruction sets
+    ArmAndThumb,
+}
+
+/// Which exclusive load/store instructions are implemented
+///
+/// Each variant implies support for everything in the variants before it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Exclusives {
+    /// No exclusive access instructions
+    None,
+    /// `LDREX`/`STREX` on words only
+    Word,
+    /// Adds `CLREX` and `LDREXB`/`LDREXH`/`STREXB`/`STREXH`
+    ByteHalfwordWord,
+    /// Adds `LDREXD`/`STREXD`
+    Doubleword,
+}
+
+/// Which version of the Performance Monitors Extension is implemented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PmuVersion {
+    /// No architected PMU
+    None,
+    /// PMUv1
+    V1,
+    /// PMUv2
+    V2,
+    /// PMUv3 (or a later revision of it)
+    V3,
+    /// An IMPLEMENTATION DEFINED PMU
+    ImplementationDefined,
+}
+
+/// Which version of the Debug architecture is implemented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DebugVersion {
+    /// No debug support, through either CP14 or memory-mapped registers
+    None,
+    /// Armv6 debug
+    V6,
+    /// Armv6.1 debug
+    V6_1,
+    /// Armv7 debug
+    V7,
+    /// Armv7.1 debug
+    V7_1,
+    /// Armv8 debug (or a later revision of it)
+    V8,
+}
+
+/// Which Virtual Memory System Architecture is implemented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Vmsa {
+    /// No VMSA (i.e. no MMU)
+    None,
+    /// An IMPLEMENTATION DEFINED VMSA
+    ImplementationDefined,
+    /// VMSAv6, with cache and TLB type registers
+    V6,
+    /// VMSAv7, with support for remapping and the Access flag
+    V7,
+    /// VMSAv7 with the PXN bit in short descriptors
+    V7Pxn,
+    /// VMSAv7 with PXN and the Long-descriptor translation table format
+    V7Lpae,
+}
+
+/// Which Protected Memory System Architecture is implemented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pmsa {
+    /// No PMSA (i.e. no MPU)
+    None,
+    /// An IMPLEMENTATION DEFINED PMSA
+    ImplementationDefined,
+    /// PMSAv6
+    V6,
+    /// PMSAv7
+    V7,
+    /// PMSAv8
+    V8,
+}
+
+/// The decoded capabilities of a processor
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuFeatures {
+    /// Is the Arm (A32) instruction set implemented?
+    pub arm: bool,
+    /// Is Thumb-2 (i.e. Thumb with 32-bit instructions) implemented?
+    pub thumb2: bool,
+    /// Which hardware divide instructions are implemented
+    pub divide: HardwareDivide,
+    /// Which exclusive load/store instructions are implemented
+    pub exclusives: Exclusives,
+    /// Are the Security Extensions implemented?
+    pub security_extensions: bool,
+    /// Are the Virtualization Extensions implemented?
+    pub virtualization_extensions: bool,
+    /// Is the Generic Timer implemented?
+    pub generic_timer: bool,
+    /// Which PMU version is implemented
+    pub pmu: PmuVersion,
+    /// Which Debug architecture version is implemented
+    pub debug: DebugVersion,
+    /// Which VMSA is implemented
+    pub vmsa: Vmsa,
+    /// Which PMSA is implemented
+    pub pmsa: Pmsa,
+    /// Is the Large Physical Address Extension implemented?
+    pub lpae: bool,
+}
+
+/// Extract the 4-bit ID register field starting at `shift`
+const fn field(value: u32, shift: u32) -> u8 {
+    ((value >> shift) & 0xF) as u8
+}
+
+impl CpuFeatures {
+    /// Decode the feature set from the given ID register values
+    ///
+    /// This does not touch the hardware, so it can be used on values captured
+    /// elsewhere (e.g. in a crash dump, or on the host in a test).
+    pub const fn decode(
+        pfr0: IdPfr0,
+        pfr1: IdPfr1,
+        dfr0: IdDfr0,
+        mmfr0: IdMmfr0,
+        isar0: IdIsar0,
+        isar3: IdIsar3,
+        isar4: IdIsar4,
+    ) -> CpuFeatures {
+        let divide = match field(isar0.0, 24) {
+            0 => HardwareDivide::None,
+            1 => HardwareDivide::ThumbOnly,
+            _ => HardwareDivide::ArmAndThumb,
+        };
+        let exclusives = match (field(isar3.0, 12), field(isar4.0, 20)) {
+            (0, _) => Exclusives::None,
+            (1, 0) => Exclusives::Word,
+            (1, _) => Exclusives::ByteHalfwordWord,
+            _ => Exclusives::Doubleword,
+        };
+        let pmu = match field(dfr0.0, 24) {
+            0 => PmuVersion::None,
+            1 => PmuVersion::V1,
+            2 => PmuVersion::V2,
+            0xF => PmuVersion::ImplementationDefined,
+            _ => PmuVersion::V3,
+        };
+        // Some cores (e.g. the Cortex-R5) only have memory-mapped debug
+        // registers, so fall back to MMapDbg when CopDbg is zero
+        let debug = match (field(dfr0.0, 0), field(dfr0.0, 8)) {
+            (0 | 1, 4) => DebugVersion::V7,
+            (0 | 1, 5) => DebugVersion::V7_1,
+            (0 | 1, _) => DebugVersion::None,
+            (2, _) => DebugVersion::V6,
+            (3, _) => DebugVersion::V6_1,
+            (4, _) => DebugVersion::V7,
+            (5, _) => DebugVersion::V7_1,
+            _ => DebugVersion::V8,
+        };
+        let vmsa = match field(mmfr0.0, 0) {
+            0 => Vmsa::None,
+            1 => Vmsa::ImplementationDefined,
+            2 => Vmsa::V6,
+            3 => Vmsa::V7,
+            4 => Vmsa::V7Pxn,
+            _ => Vmsa::V7Lpae,
+        };
+        let pmsa = match field(mmfr0.0, 4) {
+            0 => Pmsa::None,
+            1 => Pmsa::ImplementationDefined,
+            2 => Pmsa::V6,
+            3 => Pmsa::V7,
+            _ => Pmsa::V8,
+        };
+        CpuFeatures {
+            arm: field(pfr0.0, 0) != 0,
+            thumb2: field(pfr0.0, 4) == 3,
+            divide,
+            exclusives,
+            security_extensions: field(pfr1.0, 4) != 0,
+            virtualization_extensions: field(pfr1.0, 12) != 0,
+            generic_timer: field(pfr1.0, 16) != 0,
+            pmu,
+            debug,
+            vmsa,
+            pmsa,
+            lpae: matches!(vmsa, Vmsa::V7Lpae),
+        }
+    }
+}
+
+/// Read the ID registers and decode the features of the current CPU
+pub fn cpu_features() -> CpuFeatures {
+    CpuFeatures::decode(
+        IdPfr0::read(),
+        IdPfr1::read(),
+        IdDfr0::read(),
+        IdMmfr0::read(),
+        IdIsar0::read(),
+        IdIsar3::read(),
+        IdIsar4::read(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cortex_a15() {
+        // Reset values from the Cortex-A15 TRM
+        let features = CpuFeatures::decode(
+            IdPfr0(0x0000_1131),
+            IdPfr1(0x0001_1131),
+            IdDfr0(0x0201_0555),
+            IdMmfr0(0x1020_1105),
+            IdIsar0(0x0210_1110),
+            IdIsar3(0x1311_2111),
+            IdIsar4(0x1001_1142),
+        );
+        assert_eq!(
+            features,
+            CpuFeatures {
+                arm: true,
+                thumb2: true,
+                divide: HardwareDivide::ArmAndThumb,
+                exclusives: Exclusives::Doubleword,
+                security_extensions: true,
+                virtualization_extensions: true,
+                generic_timer: true,
+                pmu: PmuVersion::V2,
+                debug: DebugVersion::V7_1,
+                vmsa: Vmsa::V7Lpae,
+                pmsa: Pmsa::None,
+                lpae: true,
+            }
+        );
+    }
+
+    #[test]
+    fn cortex_r5() {
+        // Reset values from the Cortex-R5 TRM
+        let features = CpuFeatures::decode(
+            IdPfr0(0x0000_0131),
+            IdPfr1(0x0000_0001),
+            IdDfr0(0x0001_0400),
+            IdMmfr0(0x0021_0030),
+            IdIsar0(0x0210_1111),
+            IdIsar3(0x0111_2131),
+            IdIsar4(0x0001_0142),
+        );
+        assert_eq!(features.divide, HardwareDivide::ArmAndThumb);
+        assert_eq!(features.exclusives, Exclusives::Doubleword);
+        assert!(features.thumb2);
+        assert!(!features.security_extensions);
+        assert!(!features.generic_timer);
+        assert_eq!(features.vmsa, Vmsa::None);
+        assert_eq!(features.pmsa, Pmsa::V7);
+        assert!(!features.lpae);
+        // Memory-mapped debug only
+        assert_eq!(features.debug, DebugVersion::V7);
+    }
+}
@@ -22,19 +22,39 @@ pub mod asm;
 pub mod asm;
 
 pub mod cache;
+pub mod evaluate;
+#[cfg(any(
+    test,
+    doc,
+    arm_architecture = "v6",
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub mod features;
 pub mod interrupt;
+pub mod memory;
 pub mod mmu;
 pub mod register;
 #[cfg(target_arch = "arm")]
 pub mod stacks;
 
-#[cfg(any(test, doc, arm_architecture = "v7-r"))]
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v7-r"))]
 pub mod pmsav7;
 
+#[cfg(any(
+    test,
+    doc,
+    arm_architecture = "v7-a",
+    arm_architecture = "v7-r",
+    arm_architecture = "v8-r"
+))]
+pub mod pmu;
+
 #[cfg(any(test, doc, arm_architecture = "v8-r"))]
 pub mod generic_timer;
 
-#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
 pub mod pmsav8;
 
 /// Generate an SVC call with no parameters.
@@ -0,0 +1,548 @@
+//! Descriptions of memory which do not depend on the MMU or MPU
+//!
+//! [`Attributes`] describes the type, cacheability and shareability of some
+//! memory in one way, and converts to and from the encodings used by the
+//! [`mmu`](crate::mmu), [`pmsav7`](crate::pmsav7) and
+//! [`pmsav8`](crate::pmsav8) modules. A conversion which would have to drop
+//! or change part of the description fails with [`UnsupportedAttributes`]
+//! instead.
+
+use crate::mmu::{CacheableMemoryAttribute, MemoryRegionAttributes};
+
+pub mod map;
+
+/// How a cacheable level of the memory system treats some memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CachePolicy {
+    /// Write-Back (rather than Write-Through)
+    pub write_back: bool,
+    /// Allocate a cache line on a read miss
+    pub read_allocate: bool,
+    /// Allocate a cache line on a write miss
+    pub write_allocate: bool,
+    /// The data is only expected to be used for a short time
+    pub transient: bool,
+}
+
+impl CachePolicy {
+    /// Write-Back, Read-Allocate and Write-Allocate
+    pub const WRITE_BACK_WRITE_ALLOCATE: CachePolicy = CachePolicy {
+        write_back: true,
+        read_allocate: true,
+        write_allocate: true,
+        transient: false,
+    };
+    /// Write-Back, Read-Allocate and no Write-Allocate
+    pub const WRITE_BACK: CachePolicy = CachePolicy {
+        write_allocate: false,
+        ..CachePolicy::WRITE_BACK_WRITE_ALLOCATE
+    };
+    /// Write-Through, Read-Allocate and no Write-Allocate
+    pub const WRITE_THROUGH: CachePolicy = CachePolicy {
+        write_back: false,
+        ..CachePolicy::WRITE_BACK
+    };
+}
+
+/// Whether, and how, one level of the memory system caches some memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cacheability {
+    /// Not cached
+    NonCacheable,
+    /// Cached, with the given policy
+    Cacheable(CachePolicy),
+}
+
+/// Which other observers must see a coherent view of some Normal memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shareability {
+    /// Only this processor
+    NonShareable,
+    /// Processors in the same Inner Shareable domain
+    InnerShareable,
+    /// Everything in the same Outer Shareable domain
+    OuterShareable,
+}
+
+/// The memory type and attributes of some memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Attributes {
+    /// Strongly-ordered memory (Device-nGnRnE on Armv8)
+    StronglyOrdered,
+    /// Device memory (Device-nGnRE on Armv8)
+    Device {
+        /// Is the memory shareable?
+        shareable: bool,
+    },
+    /// Normal memory
+    Normal {
+        /// Cacheability in the inner cache levels
+        inner: Cacheability,
+        /// Cacheability in the outer cache levels
+        outer: Cacheability,
+        /// Shareability
+        shareability: Shareability,
+    },
+}
+
+/// The parts of some [`Attributes`] that a backend cannot express
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnsupportedAttributes {
+    /// The backend cannot express this shareability
+    Shareability(Shareability),
+    /// The backend cannot express this cache policy
+    CachePolicy(CachePolicy),
+    /// The backend cannot express Device memory with this shareability
+    DeviceShareability(bool),
+    /// The encoding is IMPLEMENTATION DEFINED, or depends on other registers
+    ImplementationDefined,
+}
+
+impl Attributes {
+    /// Normal, Non-shareable, Write-Back Write-Allocate memory
+    pub const NORMAL: Attributes = Attributes::Normal {
+        inner: Cacheability::Cacheable(CachePolicy::WRITE_BACK_WRITE_ALLOCATE),
+        outer: Cacheability::Cacheable(CachePolicy::WRITE_BACK_WRITE_ALLOCATE),
+        shareability: Shareability::NonShareable,
+    };
+
+    /// Normal, Non-shareable, Non-cacheable memory
+    pub const NON_CACHEABLE: Attributes = Attributes::Normal {
+        inner: Cacheability::NonCacheable,
+        outer: Cacheability::NonCacheable,
+        shareability: Shareability::NonShareable,
+    };
+
+    /// Convert to Short-descriptor TEX, C and B bits, and the S bit
+    ///
+    /// This assumes SCTLR.TRE is clear, where shareable Normal memory is
+    /// Outer Shareable.
+    pub const fn to_mmu(&self) -> Result<(MemoryRegionAttributes, bool), UnsupportedAttributes> {
+        match *self {
+            Attributes::StronglyOrdered => Ok((MemoryRegionAttributes::StronglyOrdered, true)),
+            Attributes::Device { shareable: true } => {
+                Ok((MemoryRegionAttributes::ShareableDevice, true))
+            }
+            Attributes::Device { shareable: false } => {
+                Ok((MemoryRegionAttributes::NonShareableDevice, false))
+            }
+            Attributes::Normal {
+                inner,
+                outer,
+                shareability,
+            } => {
+                let shareable = match short_shareable(shareability) {
+                    Ok(s) => s,
+                    Err(e) => return Err(e),
+                };
+                let inner = match short_policy(inner) {
+                    Ok(p) => p,
+                    Err(e) => return Err(e),
+                };
+                let outer = match short_policy(outer) {
+                    Ok(p) => p,
+                    Err(e) => return Err(e),
+                };
+                Ok((
+                    MemoryRegionAttributes::CacheableMemory {
+                        inner: CacheableMemoryAttribute::new_with_raw_value(
+                            arbitrary_int::u2::new(inner),
+                        ),
+                        outer: CacheableMemoryAttribute::new_with_raw_value(
+                            arbitrary_int::u2::new(outer),
+                        ),
+                    },
+                    shareable,
+                ))
+            }
+        }
+    }
+
+    /// Convert from Short-descriptor attributes and the S bit
+    ///
+    /// [`MemoryRegionAttributes::Remapped`] depends on PRRR and NMRR, so it
+    /// gives [`UnsupportedAttributes::ImplementationDefined`].
+    pub const fn from_mmu(
+        attrs: &MemoryRegionAttributes,
+        shareable: bool,
+    ) -> Result<Attributes, UnsupportedAttributes> {
+        let (inner, outer) = match attrs {
+            MemoryRegionAttributes::StronglyOrdered => return Ok(Attributes::StronglyOrdered),
+            MemoryRegionAttributes::ShareableDevice => {
+                return Ok(Attributes::Device { shareable: true })
+            }
+            MemoryRegionAttributes::NonShareableDevice => {
+                return Ok(Attributes::Device { shareable: false })
+            }
+            MemoryRegionAttributes::Remapped { .. } => {
+                return Err(UnsupportedAttributes::ImplementationDefined)
+            }
+            MemoryRegionAttributes::OuterAndInnerWriteThroughNoWriteAlloc => (0b10, 0b10),
+            MemoryRegionAttributes::OuterAndInnerWriteBackNoWriteAlloc => (0b11, 0b11),
+            MemoryRegionAttributes::OuterAndInnerNonCacheable => (0b00, 0b00),
+            MemoryRegionAttributes::OuterAndInnerWriteBackWriteAlloc => (0b01, 0b01),
+            MemoryRegionAttributes::CacheableMemory { inner, outer } => {
+                (*inner as u8, *outer as u8)
+            }
+        };
+        Ok(Attributes::Normal {
+            inner: from_short_policy(inner),
+            outer: from_short_policy(outer),
+            shareability: if shareable {
+                Shareability::OuterShareable
+            } else {
+                Shareability::NonShareable
+            },
+        })
+    }
+}
+
+/// Convert to the S bit of the Short-descriptor and PMSAv7 formats
+const fn short_shareable(shareability: Shareability) -> Result<bool, UnsupportedAttributes> {
+    match shareability {
+        Shareability::NonShareable => Ok(false),
+        Shareability::OuterShareable => Ok(true),
+        Shareability::InnerShareable => Err(UnsupportedAttributes::Shareability(shareability)),
+    }
+}
+
+/// Convert to the two-bit cache policy of the Short-descriptor and PMSAv7 formats
+const fn short_policy(cacheability: Cacheability) -> Result<u8, UnsupportedAttributes> {
+    match cacheability {
+        Cacheability::NonCacheable => Ok(0b00),
+        Cacheability::Cacheable(policy) => match policy {
+            CachePolicy {
+                write_back: true,
+                read_allocate: true,
+                write_allocate: true,
+                transient: false,
+            } => Ok(0b01),
+            CachePolicy {
+                write_back: false,
+                read_allocate: true,
+                write_allocate: false,
+                transient: false,
+            } => Ok(0b10),
+            CachePolicy {
+                write_back: true,
+                read_allocate: true,
+                write_allocate: false,
+                transient: false,
+            } => Ok(0b11),
+            _ => Err(UnsupportedAttributes::CachePolicy(policy)),
+        },
+    }
+}
+
+/// Convert from the two-bit cache policy of the Short-descriptor and PMSAv7 formats
+const fn from_short_policy(bits: u8) -> Cacheability {
+    match bits {
+        0b01 => Cacheability::Cacheable(CachePolicy::WRITE_BACK_WRITE_ALLOCATE),
+        0b10 => Cacheability::Cacheable(CachePolicy::WRITE_THROUGH),
+        0b11 => Cacheability::Cacheable(CachePolicy::WRITE_BACK),
+        _ => Cacheability::NonCacheable,
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v7-r"))]
+impl Attributes {
+    /// Convert to a PMSAv7 region's memory attributes
+    ///
+    /// Shareable Normal memory must be Outer Shareable.
+    pub const fn to_pmsav7(&self) -> Result<crate::pmsav7::MemAttr, UnsupportedAttributes> {
+        use crate::pmsav7::{CacheablePolicy, MemAttr};
+
+        match *self {
+            Attributes::StronglyOrdered => Ok(MemAttr::StronglyOrdered),
+            Attributes::Device { shareable } => Ok(MemAttr::Device { shareable }),
+            Attributes::Normal {
+                inner,
+                outer,
+                shareability,
+            } => {
+                let shareable = match short_shareable(shareability) {
+                    Ok(s) => s,
+                    Err(e) => return Err(e),
+                };
+                let inner = match short_policy(inner) {
+                    Ok(p) => p,
+                    Err(e) => return Err(e),
+                };
+                let outer = match short_policy(outer) {
+                    Ok(p) => p,
+                    Err(e) => return Err(e),
+                };
+                Ok(MemAttr::Cacheable {
+                    outer: CacheablePolicy::new_with_raw_value(arbitrary_int::u2::new(outer)),
+                    inner: CacheablePolicy::new_with_raw_value(arbitrary_int::u2::new(inner)),
+                    shareable,
+                })
+            }
+        }
+    }
+
+    /// Convert from a PMSAv7 region's memory attributes
+    pub const fn from_pmsav7(
+        mem_attr: &crate::pmsav7::MemAttr,
+    ) -> Result<Attributes, UnsupportedAttributes> {
+        use crate::pmsav7::MemAttr;
+
+        let (inner, outer, shareable) = match *mem_attr {
+            MemAttr::StronglyOrdered => return Ok(Attributes::StronglyOrdered),
+            MemAttr::Device { shareable } => return Ok(Attributes::Device { shareable }),
+            MemAttr::ImplementationDefined { .. } => {
+                return Err(UnsupportedAttributes::ImplementationDefined)
+            }
+            MemAttr::WriteThroughNoWriteAllocate { shareable } => (0b10, 0b10, shareable),
+            MemAttr::WriteBackNoWriteAllocate { shareable } => (0b11, 0b11, shareable),
+            MemAttr::NonCacheable { shareable } => (0b00, 0b00, shareable),
+            MemAttr::WriteBackWriteAllocate { shareable } => (0b01, 0b01, shareable),
+            MemAttr::Cacheable {
+                outer,
+                inner,
+                shareable,
+            } => (inner as u8, outer as u8, shareable),
+        };
+        Ok(Attributes::Normal {
+            inner: from_short_policy(inner),
+            outer: from_short_policy(outer),
+            shareability: if shareable {
+                Shareability::OuterShareable
+            } else {
+                Shareability::NonShareable
+            },
+        })
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+impl Attributes {
+    /// Convert to a PMSAv8-32 memory attribute, and the shareability for the region
+    ///
+    /// Device memory is always shareable on Armv8, so non-shareable Device
+    /// memory is rejected.
+    pub const fn to_pmsav8(
+        &self,
+    ) -> Result<(crate::pmsav8::MemAttr, Shareability), UnsupportedAttributes> {
+        use crate::pmsav8::MemAttr;
+
+        match *self {
+            Attributes::StronglyOrdered => {
+                Ok((MemAttr::StronglyOrdered, Shareability::OuterShareable))
+            }
+            Attributes::Device { shareable: true } => {
+                Ok((MemAttr::DeviceMemory, Shareability::OuterShareable))
+            }
+            Attributes::Device { shareable: false } => {
+                Err(UnsupportedAttributes::DeviceShareability(false))
+            }
+            Attributes::Normal {
+                inner,
+                outer,
+                shareability,
+            } => {
+                let inner = match pmsav8_cacheable(inner) {
+                    Ok(c) => c,
+                    Err(e) => return Err(e),
+                };
+                let outer = match pmsav8_cacheable(outer) {
+                    Ok(c) => c,
+                    Err(e) => return Err(e),
+                };
+                Ok((MemAttr::NormalMemory { outer, inner }, shareability))
+            }
+        }
+    }
+
+    /// Convert from a PMSAv8-32 memory attribute and the shareability of a region
+    ///
+    /// The shareability is ignored for Device and Strongly-ordered memory.
+    pub const fn from_pmsav8(
+        mem_attr: &crate::pmsav8::MemAttr,
+        shareability: Shareability,
+    ) -> Attributes {
+        use crate::pmsav8::MemAttr;
+
+        match mem_attr {
+            MemAttr::StronglyOrdered => Attributes::StronglyOrdered,
+            MemAttr::DeviceMemory => Attributes::Device { shareable: true },
+            MemAttr::NormalMemory { outer, inner } => Attributes::Normal {
+                inner: from_pmsav8_cacheable(inner),
+                outer: from_pmsav8_cacheable(outer),
+                shareability,
+            },
+        }
+    }
+}
+
+/// Convert to a PMSAv8-32 cache policy
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+const fn pmsav8_cacheable(
+    cacheability: Cacheability,
+) -> Result<crate::pmsav8::Cacheable, UnsupportedAttributes> {
+    use crate::pmsav8::{Cacheable, RwAllocPolicy};
+
+    let policy = match cacheability {
+        Cacheability::NonCacheable => return Ok(Cacheable::NonCacheable),
+        Cacheability::Cacheable(policy) => policy,
+    };
+    let alloc = match (policy.read_allocate, policy.write_allocate) {
+        (true, true) => RwAllocPolicy::RW,
+        (true, false) => RwAllocPolicy::R,
+        (false, true) => RwAllocPolicy::W,
+        (false, false) => return Err(UnsupportedAttributes::CachePolicy(policy)),
+    };
+    Ok(match (policy.write_back, policy.transient) {
+        (false, true) => Cacheable::WriteThroughTransient(alloc),
+        (true, true) => Cacheable::WriteBackTransient(alloc),
+        (false, false) => Cacheable::WriteThroughNonTransient(alloc),
+        (true, false) => Cacheable::WriteBackNonTransient(alloc),
+    })
+}
+
+/// Convert from a PMSAv8-32 cache policy
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+const fn from_pmsav8_cacheable(cacheable: &crate::pmsav8::Cacheable) -> Cacheability {
+    use crate::pmsav8::{Cacheable, RwAllocPolicy};
+
+    let (write_back, transient, alloc) = match *cacheable {
+        Cacheable::NonCacheable => return Cacheability::NonCacheable,
+        Cacheable::WriteThroughTransient(alloc) => (false, true, alloc),
+        Cacheable::WriteBackTransient(alloc) => (true, true, alloc),
+        Cacheable::WriteThroughNonTransient(alloc) => (false, false, alloc),
+        Cacheable::WriteBackNonTransient(alloc) => (true, false, alloc),
+    };
+    Cacheability::Cacheable(CachePolicy {
+        write_back,
+        read_allocate: matches!(alloc, RwAllocPolicy::R | RwAllocPolicy::RW),
+        write_allocate: matches!(alloc, RwAllocPolicy::W | RwAllocPolicy::RW),
+        transient,
+    })
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+impl Shareability {
+    /// Convert to the shareability of an EL1 MPU region
+    pub(crate) const fn to_el1(self) -> crate::pmsav8::El1Shareability {
+        use crate::pmsav8::El1Shareability;
+
+        match self {
+            Shareability::NonShareable => El1Shareability::NonShareable,
+            Shareability::InnerShareable => El1Shareability::InnerShareable,
+            Shareability::OuterShareable => El1Shareability::OuterShareable,
+        }
+    }
+
+    /// Convert to the shareability of an EL2 MPU region
+    pub(crate) const fn to_el2(self) -> crate::pmsav8::El2Shareability {
+        use crate::pmsav8::El2Shareability;
+
+        match self {
+            Shareability::NonShareable => El2Shareability::NonShareable,
+            Shareability::InnerShareable => El2Shareability::InnerShareable,
+            Shareability::OuterShareable => El2Shareability::OuterShareable,
+        }
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+impl From<Shareability> for crate::pmsav8::El1Shareability {
+    fn from(shareability: Shareability) -> Self {
+        shareability.to_el1()
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+impl From<Shareability> for crate::pmsav8::El2Shareability {
+    fn from(shareability: Shareability) -> Self {
+        shareability.to_el2()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WRITE_THROUGH_TRANSIENT: Attributes = Attributes::Normal {
+        inner: Cacheability::Cacheable(CachePolicy {
+            transient: true,
+            ..CachePolicy::WRITE_THROUGH
+        }),
+        outer: Cacheability::NonCacheable,
+        shareability: Shareability::InnerShareable,
+    };
+
+    const MIXED: Attributes = Attributes::Normal {
+        inner: Cacheability::Cacheable(CachePolicy::WRITE_BACK_WRITE_ALLOCATE),
+        outer: Cacheability::Cacheable(CachePolicy::WRITE_THROUGH),
+        shareability: Shareability::OuterShareable,
+    };
+
+    #[test]
+    fn round_trips() {
+        for attrs in [
+            Attributes::StronglyOrdered,
+            Attributes::Device { shareable: true },
+            Attributes::NORMAL,
+            Attributes::NON_CACHEABLE,
+            MIXED,
+        ] {
+            let (mmu, s) = attrs.to_mmu().unwrap();
+            assert_eq!(Attributes::from_mmu(&mmu, s), Ok(attrs));
+            let pmsav7 = attrs.to_pmsav7().unwrap();
+            assert_eq!(Attributes::from_pmsav7(&pmsav7), Ok(attrs));
+            let (pmsav8, shareability) = attrs.to_pmsav8().unwrap();
+            assert_eq!(Attributes::from_pmsav8(&pmsav8, shareability), attrs);
+        }
+        let (pmsav8, shareability) = WRITE_THROUGH_TRANSIENT.to_pmsav8().unwrap();
+        assert_eq!(
+            Attributes::from_pmsav8(&pmsav8, shareability),
+            WRITE_THROUGH_TRANSIENT
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(matches!(
+            WRITE_THROUGH_TRANSIENT.to_mmu(),
+            Err(UnsupportedAttributes::Shareability(
+                Shareability::InnerShareable
+            ))
+        ));
+        let transient = Attributes::Normal {
+            inner: Cacheability::Cacheable(CachePolicy {
+                transient: true,
+                ..CachePolicy::WRITE_THROUGH
+            }),
+            outer: Cacheability::NonCacheable,
+            shareability: Shareability::NonShareable,
+        };
+        assert!(matches!(
+            transient.to_pmsav7(),
+            Err(UnsupportedAttributes::CachePolicy(_))
+        ));
+        assert_eq!(
+            Attributes::Device { shareable: false }.to_pmsav8(),
+            Err(UnsupportedAttributes::DeviceShareability(false))
+        );
+        assert_eq!(
+            Attributes::from_mmu(
+                &MemoryRegionAttributes::Remapped {
+                    index: arbitrary_int::u3::new(1)
+                },
+                false
+            ),
+            Err(UnsupportedAttributes::ImplementationDefined)
+        );
+    }
+}
@@ -0,0 +1,670 @@
+//! One memory map, lowered to MMU translation tables or MPU regions
+//!
+//! A [`MemoryMap`] lists the interesting ranges of a system's address space
+//! and what kind of memory each one is. The same map can be turned into an
+//! identity mapped [`L1Table`], PMSAv7 regions or PMSAv8-32 regions, in
+//! `const` context, so an SoC with both Cortex-A and Cortex-R cores only needs
+//! describing once. Each lowering panics if the map breaks the rules of the
+//! target, which makes a bad map a compile error when the result is assigned
+//! to a `const` or `static`.
+//!
+//! ```rust
+//! use aarch32_cpu::memory::map::{MemoryKind, MemoryMap, MemoryRange};
+//! use aarch32_cpu::mmu::L1Table;
+//!
+//! const MAP: MemoryMap = MemoryMap::new(&[
+//!     MemoryRange::new("flash", 0x0000_0000, 0x0040_0000, MemoryKind::Executable),
+//!     MemoryRange::new("sram", 0x2000_0000, 0x0010_0000, MemoryKind::Normal),
+//!     MemoryRange::new("uart", 0x4000_0000, 0x0010_0000, MemoryKind::Device),
+//! ]);
+//!
+//! static TABLE: L1Table = MAP.l1_table();
+//! ```
+//!
+//! A range which is not made of whole 1 MiB sections cannot go in an L1 table:
+//!
+//! ```rust,compile_fail
+//! # use aarch32_cpu::memory::map::{MemoryKind, MemoryMap, MemoryRange};
+//! # use aarch32_cpu::mmu::L1Table;
+//! const MAP: MemoryMap = MemoryMap::new(&[
+//!     MemoryRange::new("sram", 0x2000_0000, 0x0002_0000, MemoryKind::Normal),
+//! ]);
+//!
+//! static TABLE: L1Table = MAP.l1_table();
+//! ```
+
+use super::Attributes;
+use crate::mmu::{
+    AccessPermissions, L1Section, L1Table, MemoryRegionAttributes, SectionAttributes,
+    NUM_L1_PAGE_TABLE_ENTRIES,
+};
+
+/// What a range of memory holds, and so how it may be accessed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryKind {
+    /// Cacheable read-write data, which cannot be executed
+    Normal,
+    /// Memory-mapped peripherals, which cannot be executed
+    Device,
+    /// Cacheable read-only data, which cannot be executed
+    ReadOnly,
+    /// Cacheable read-only code
+    Executable,
+}
+
+impl MemoryKind {
+    /// The memory type and attributes used for this kind of memory
+    ///
+    /// Device memory is shareable. Everything else is [`Attributes::NORMAL`].
+    pub const fn attributes(self) -> Attributes {
+        match self {
+            MemoryKind::Device => Attributes::Device { shareable: true },
+            MemoryKind::Normal | MemoryKind::ReadOnly | MemoryKind::Executable => {
+                Attributes::NORMAL
+            }
+        }
+    }
+
+    /// The Short-descriptor attributes and S bit for this kind of memory
+    const fn mmu_attributes(self) -> (MemoryRegionAttributes, bool) {
+        match self.attributes().to_mmu() {
+            Ok(attrs) => attrs,
+            Err(_) => panic!("memory kind has no Short-descriptor encoding"),
+        }
+    }
+
+    /// The PMSAv7 attributes for this kind of memory
+    #[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v7-r"))]
+    const fn pmsav7_attributes(self) -> crate::pmsav7::MemAttr {
+        match self.attributes().to_pmsav7() {
+            Ok(attrs) => attrs,
+            Err(_) => panic!("memory kind has no PMSAv7 encoding"),
+        }
+    }
+
+    /// The PMSAv8-32 attributes and shareability for this kind of memory
+    #[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+    const fn pmsav8_attributes(self) -> (crate::pmsav8::MemAttr, super::Shareability) {
+        match self.attributes().to_pmsav8() {
+            Ok(attrs) => attrs,
+            Err(_) => panic!("memory kind has no PMSAv8-32 encoding"),
+        }
+    }
+
+    const fn writable(self) -> bool {
+        matches!(self, MemoryKind::Normal | MemoryKind::Device)
+    }
+
+    const fn executable(self) -> bool {
+        matches!(self, MemoryKind::Executable)
+    }
+}
+
+/// A named range of memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MemoryRange {
+    /// A name for the range, for people reading the map
+    pub name: &'static str,
+    /// The first address in the range
+    pub start: u32,
+    /// The length of the range in bytes
+    pub size: u32,
+    /// What the range holds
+    pub kind: MemoryKind,
+    /// Can unprivileged (PL0 or EL0) code access the range too?
+    pub unprivileged: bool,
+}
+
+impl MemoryRange {
+    /// Describe a range which only privileged code can access
+    pub const fn new(name: &'static str, start: u32, size: u32, kind: MemoryKind) -> MemoryRange {
+        MemoryRange {
+            name,
+            start,
+            size,
+            kind,
+            unprivileged: false,
+        }
+    }
+
+    /// Let unprivileged code access the range as well
+    pub const fn with_unprivileged_access(self) -> MemoryRange {
+        MemoryRange {
+            unprivileged: true,
+            ..self
+        }
+    }
+
+    /// The address just past the end of the range
+    const fn end(&self) -> u64 {
+        self.start as u64 + self.size as u64
+    }
+}
+
+/// Ways a memory map can be unsuitable for a target
+///
+/// Ranges are identified by their index in the map.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A range has a size of zero
+    Empty {
+        /// The empty range
+        range: usize,
+    },
+    /// A range runs past the end of the 32-bit address space
+    PastEndOfMemory {
+        /// The range which is too long
+        range: usize,
+    },
+    /// A range is not aligned as the target requires
+    ///
+    /// The MMU needs 1 MiB sections, PMSAv7 needs a power-of-two size of at
+    /// least 32 bytes aligned to that size, and PMSAv8-32 needs 64 bytes.
+    Unaligned {
+        /// The badly aligned range
+        range: usize,
+    },
+    /// Two ranges share some addresses
+    Overlap {
+        /// The range which comes first in the map
+        first: usize,
+        /// The range which comes second in the map
+        second: usize,
+    },
+    /// The number of regions asked for is not the number of ranges in the map
+    WrongLength {
+        /// The number of ranges in the map
+        expected: usize,
+    },
+}
+
+impl Error {
+    /// Panic with a message describing this error
+    ///
+    /// In `const` context this turns the error into a compile error.
+    pub const fn panic(self) -> ! {
+        match self {
+            Error::Empty { .. } => panic!("memory map has an empty range"),
+            Error::PastEndOfMemory { .. } => {
+                panic!("memory map has a range past the end of the address space")
+            }
+            Error::Unaligned { .. } => {
+                panic!("memory map has a range that is not aligned for this target")
+            }
+            Error::Overlap { .. } => panic!("memory map has overlapping ranges"),
+            Error::WrongLength { .. } => {
+                panic!("number of regions does not match the number of ranges in the memory map")
+            }
+        }
+    }
+}
+
+/// The alignment rules of each target
+#[derive(Copy, Clone)]
+enum Granule {
+    /// Multiples of 1 MiB
+    Section,
+    /// A power of two, aligned to itself
+    PowerOfTwo,
+    /// Multiples of 64 bytes
+    Bytes64,
+}
+
+/// The size of an L1 section
+const SECTION_SIZE: u32 = 1024 * 1024;
+
+/// The smallest PMSAv7 region
+const MIN_PMSAV7_REGION_SIZE: u32 = 32;
+
+/// The alignment of a PMSAv8-32 region
+const PMSAV8_ALIGN: u32 = 64;
+
+/// A description of a system's address space
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MemoryMap<'a> {
+    /// The ranges in the map
+    pub ranges: &'a [MemoryRange],
+}
+
+impl<'a> MemoryMap<'a> {
+    /// Make a memory map from some ranges
+    pub const fn new(ranges: &'a [MemoryRange]) -> MemoryMap<'a> {
+        MemoryMap { ranges }
+    }
+
+    /// Check the map can be lowered with [`MemoryMap::l1_table`]
+    pub const fn check_mmu(&self) -> Result<(), Error> {
+        self.check(Granule::Section)
+    }
+
+    /// Check the map can be lowered with [`MemoryMap::pmsav7_regions`]
+    pub const fn check_pmsav7(&self) -> Result<(), Error> {
+        self.check(Granule::PowerOfTwo)
+    }
+
+    /// Check the map can be lowered with [`MemoryMap::el1_regions`] or
+    /// [`MemoryMap::el2_regions`]
+    pub const fn check_pmsav8(&self) -> Result<(), Error> {
+        self.check(Granule::Bytes64)
+    }
+
+    const fn check(&self, granule: Granule) -> Result<(), Error> {
+        let mut i = 0;
+        while i < self.ranges.len() {
+            let range = &self.ranges[i];
+            if range.size == 0 {
+                return Err(Error::Empty { range: i });
+            }
+            if range.end() > 1 << 32 {
+                return Err(Error::PastEndOfMemory { range: i });
+            }
+            let aligned = match granule {
+                Granule::Section => {
+                    range.start % SECTION_SIZE == 0 && range.size % SECTION_SIZE == 0
+                }
+                Granule::PowerOfTwo => {
+                    range.size.is_power_of_two()
+                        && range.size >= MIN_PMSAV7_REGION_SIZE
+                        && range.start % range.size == 0
+                }
+                Granule::Bytes64 => {
+                    range.start % PMSAV8_ALIGN == 0 && range.size % PMSAV8_ALIGN == 0
+                }
+            };
+            if !aligned {
+                return Err(Error::Unaligned { range: i });
+            }
+            let mut j = 0;
+            while j < i {
+                let other = &self.ranges[j];
+                if (range.start as u64) < other.end() && (other.start as u64) < range.end() {
+                    return Err(Error::Overlap {
+                        first: j,
+                        second: i,
+                    });
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// Check the map, and that it has `n` ranges
+    #[cfg(any(
+        test,
+        doc,
+        not(target_arch = "arm"),
+        arm_architecture = "v7-r",
+        arm_architecture = "v8-r"
+    ))]
+    const fn check_len(&self, granule: Granule, n: usize) {
+        if let Err(e) = self.check(granule) {
+            e.panic();
+        }
+        if n != self.ranges.len() {
+            Error::WrongLength {
+                expected: self.ranges.len(),
+            }
+            .panic();
+        }
+    }
+
+    /// Build an identity mapped L1 table, with 1 MiB sections in domain 0
+    ///
+    /// Addresses outside the map are left unmapped.
+    ///
+    /// # Panics
+    ///
+    /// If [`MemoryMap::check_mmu`] fails.
+    pub const fn l1_table(&self) -> L1Table {
+        if let Err(e) = self.check_mmu() {
+            e.panic();
+        }
+        let mut entries = [L1Section::new_with_raw_value(0); NUM_L1_PAGE_TABLE_ENTRIES];
+        let mut i = 0;
+        while i < self.ranges.len() {
+            let range = &self.ranges[i];
+            let attrs = section_attributes(range);
+            let mut addr = range.start as u64;
+            while addr < range.end() {
+                entries[(addr / SECTION_SIZE as u64) as usize] =
+                    L1Section::new_with_addr_and_attrs(addr as u32, attrs);
+                addr += SECTION_SIZE as u64;
+            }
+            i += 1;
+        }
+        L1Table {
+            entries: core::cell::UnsafeCell::new(entries),
+        }
+    }
+}
+
+/// The section attributes for a range
+const fn section_attributes(range: &MemoryRange) -> SectionAttributes {
+    let access = match (range.kind.writable(), range.unprivileged) {
+        (true, false) => AccessPermissions::PrivilegedOnly,
+        (true, true) => AccessPermissions::FullAccess,
+        (false, false) => AccessPermissions::PrivilegedReadOnly,
+        (false, true) => AccessPermissions::ReadOnly,
+    };
+    let (memory_attrs, shareable) = range.kind.mmu_attributes();
+    SectionAttributes {
+        non_global: false,
+        p_bit: false,
+        shareable,
+        access,
+        memory_attrs: memory_attrs.as_raw(),
+        domain: arbitrary_int::u4::new(0),
+        execute_never: !range.kind.executable(),
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v7-r"))]
+impl MemoryMap<'_> {
+    /// Build one PMSAv7 region per range, for [`pmsav7::Config`](crate::pmsav7::Config)
+    ///
+    /// `N` must be the number of ranges in the map.
+    ///
+    /// # Panics
+    ///
+    /// If [`MemoryMap::check_pmsav7`] fails, or `N` is wrong.
+    pub const fn pmsav7_regions<const N: usize>(&self) -> [crate::pmsav7::Region; N] {
+        use crate::pmsav7::{AccessPerms, MemAttr, Region, RegionSize};
+
+        self.check_len(Granule::PowerOfTwo, N);
+        let mut regions = [const {
+            Region {
+                base: core::ptr::null_mut(),
+                size: RegionSize::_32B,
+                subregion_mask: 0,
+                enabled: false,
+                no_exec: true,
+                access: AccessPerms::NoAccess,
+                mem_attr: MemAttr::StronglyOrdered,
+            }
+        }; N];
+        let mut i = 0;
+        while i < N {
+            let range = &self.ranges[i];
+            regions[i] = Region {
+                base: range.start as usize as *mut u8,
+                size: RegionSize::new_with_raw_value(arbitrary_int::u5::new(
+                    (range.size.trailing_zeros() - 1) as u8,
+                )),
+                subregion_mask: 0,
+                enabled: true,
+                no_exec: !range.kind.executable(),
+                access: match (range.kind.writable(), range.unprivileged) {
+                    (true, false) => AccessPerms::PrivilegedOnly,
+                    (true, true) => AccessPerms::FullAccess,
+                    (false, false) => AccessPerms::PrivilegedReadOnly,
+                    (false, true) => AccessPerms::ReadOnly,
+                },
+                mem_attr: range.kind.pmsav7_attributes(),
+            };
+            i += 1;
+        }
+        regions
+    }
+}
+
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
+impl MemoryMap<'_> {
+    /// The memory attributes for PMSAv8-32 regions from this map
+    ///
+    /// Only the attributes the map uses are included, so that
+    /// [`validate`](crate::pmsav8::validate::validate) does not find unused
+    /// ones. Normal memory comes first, then Device memory.
+    pub const fn pmsav8_attributes(&self) -> &'static [crate::pmsav8::MemAttr] {
+        use crate::pmsav8::MemAttr;
+
+        const NORMAL: MemAttr = MemoryKind::Normal.pmsav8_attributes().0;
+        const DEVICE: MemAttr = MemoryKind::Device.pmsav8_attributes().0;
+        match (self.has_normal(), self.has_device()) {
+            (true, true) => &[NORMAL, DEVICE],
+            (true, false) => &[NORMAL],
+            (false, true) => &[DEVICE],
+            (false, false) => &[],
+        }
+    }
+
+    const fn has_device(&self) -> bool {
+        let mut i = 0;
+        while i < self.ranges.len() {
+            if matches!(self.ranges[i].kind, MemoryKind::Device) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    const fn has_normal(&self) -> bool {
+        let mut i = 0;
+        while i < self.ranges.len() {
+            if !matches!(self.ranges[i].kind, MemoryKind::Device) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// The MAIR selector for a range, matching [`MemoryMap::pmsav8_attributes`]
+    const fn pmsav8_mair(&self, range: &MemoryRange) -> u8 {
+        match range.kind {
+            MemoryKind::Device if self.has_normal() => 1,
+            _ => 0,
+        }
+    }
+
+    /// Build one EL1 MPU region per range, for [`pmsav8::El1Config`](crate::pmsav8::El1Config)
+    ///
+    /// `N` must be the number of ranges in the map. Use
+    /// [`MemoryMap::pmsav8_attributes`] for the memory attributes.
+    ///
+    /// # Panics
+    ///
+    /// If [`MemoryMap::check_pmsav8`] fails, or `N` is wrong.
+    pub const fn el1_regions<const N: usize>(&self) -> [crate::pmsav8::El1Region; N] {
+        use crate::pmsav8::{El1AccessPerms, El1Region, El1Shareability};
+
+        self.check_len(Granule::Bytes64, N);
+        let mut regions = [const {
+            El1Region {
+                range: core::ptr::null_mut()..=core::ptr::null_mut(),
+                shareability: El1Shareability::NonShareable,
+                access: El1AccessPerms::ReadOnlyNoEL0,
+                no_exec: true,
+                mair: 0,
+                enable: false,
+            }
+        }; N];
+        let mut i = 0;
+        while i < N {
+            let range = &self.ranges[i];
+            regions[i] = El1Region {
+                range: range.start as usize as *mut u8
+                    ..=(range.end() - 1) as u32 as usize as *mut u8,
+                shareability: range.kind.pmsav8_attributes().1.to_el1(),
+                access: match (range.kind.writable(), range.unprivileged) {
+                    (true, false) => El1AccessPerms::ReadWriteNoEL0,
+                    (true, true) => El1AccessPerms::ReadWrite,
+                    (false, false) => El1AccessPerms::ReadOnlyNoEL0,
+                    (false, true) => El1AccessPerms::ReadOnly,
+                },
+                no_exec: !range.kind.executable(),
+                mair: self.pmsav8_mair(range),
+                enable: true,
+            };
+            i += 1;
+        }
+        regions
+    }
+
+    /// Build one EL2 MPU region per range, for [`pmsav8::El2Config`](crate::pmsav8::El2Config)
+    ///
+    /// Privileged means EL2 here, and unprivileged means EL1 and EL0.
+    /// Otherwise this is the same as [`MemoryMap::el1_regions`].
+    ///
+    /// # Panics
+    ///
+    /// If [`MemoryMap::check_pmsav8`] fails, or `N` is wrong.
+    pub const fn el2_regions<const N: usize>(&self) -> [crate::pmsav8::El2Region; N] {
+        use crate::pmsav8::{El2AccessPerms, El2Region, El2Shareability};
+
+        self.check_len(Granule::Bytes64, N);
+        let mut regions = [const {
+            El2Region {
+                range: core::ptr::null_mut()..=core::ptr::null_mut(),
+                shareability: El2Shareability::NonShareable,
+                access: El2AccessPerms::ReadOnlyNoEL10,
+                no_exec: true,
+                mair: 0,
+                enable: false,
+            }
+        }; N];
+        let mut i = 0;
+        while i < N {
+            let range = &self.ranges[i];
+            regions[i] = El2Region {
+                range: range.start as usize as *mut u8
+                    ..=(range.end() - 1) as u32 as usize as *mut u8,
+                shareability: range.kind.pmsav8_attributes().1.to_el2(),
+                access: match (range.kind.writable(), range.unprivileged) {
+                    (true, false) => El2AccessPerms::ReadWriteNoEL10,
+                    (true, true) => El2AccessPerms::ReadWrite,
+                    (false, false) => El2AccessPerms::ReadOnlyNoEL10,
+                    (false, true) => El2AccessPerms::ReadOnly,
+                },
+                no_exec: !range.kind.executable(),
+                mair: self.pmsav8_mair(range),
+                enable: true,
+            };
+            i += 1;
+        }
+        regions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::evaluate::{Access, Evaluate, Fault};
+    use crate::mmu::evaluate::Tables;
+    use crate::register::{dacr::DomainAccess, Dacr};
+
+    const MAP: MemoryMap = MemoryMap::new(&[
+        MemoryRange::new("flash", 0x0000_0000, 0x0040_0000, MemoryKind::Executable),
+        MemoryRange::new("sram", 0x2000_0000, 0x0010_0000, MemoryKind::Normal)
+            .with_unprivileged_access(),
+        MemoryRange::new("uart", 0x4000_0000, 0x0010_0000, MemoryKind::Device),
+    ]);
+
+    #[test]
+    fn check() {
+        assert_eq!(MAP.check_mmu(), Ok(()));
+        assert_eq!(MAP.check_pmsav7(), Ok(()));
+        const OVERLAPPING: MemoryMap = MemoryMap::new(&[
+            MemoryRange::new("a", 0x0000_0000, 0x0020_0000, MemoryKind::Normal),
+            MemoryRange::new("b", 0x0010_0000, 0x0010_0000, MemoryKind::Normal),
+        ]);
+        assert_eq!(
+            OVERLAPPING.check_pmsav8(),
+            Err(Error::Overlap {
+                first: 0,
+                second: 1
+            })
+        );
+        const UNALIGNED: MemoryMap = MemoryMap::new(&[MemoryRange::new(
+            "a",
+            0x0000_0040,
+            0x0000_0080,
+            MemoryKind::Normal,
+        )]);
+        assert_eq!(UNALIGNED.check_pmsav8(), Ok(()));
+        assert_eq!(UNALIGNED.check_pmsav7(), Err(Error::Unaligned { range: 0 }));
+        assert_eq!(UNALIGNED.check_mmu(), Err(Error::Unaligned { range: 0 }));
+    }
+
+    #[test]
+    fn l1_table() {
+        static TABLE: L1Table = MAP.l1_table();
+        let tables = Tables {
+            l1: &TABLE,
+            l2: &[],
+            dacr: Dacr::new_with_all(DomainAccess::Client),
+        };
+        assert!(tables
+            .evaluate(0x0030_0000, Access::PRIVILEGED_EXECUTE)
+            .is_ok());
+        assert_eq!(
+            tables.evaluate(0x0030_0000, Access::PRIVILEGED_WRITE),
+            Err(Fault::Permission { level: 1 })
+        );
+        assert!(tables.evaluate(0x2000_0000, Access::USER_WRITE).is_ok());
+        assert_eq!(
+            tables.evaluate(0x4000_0000, Access::USER_READ),
+            Err(Fault::Permission { level: 1 })
+        );
+        assert_eq!(
+            tables.evaluate(0x4010_0000, Access::PRIVILEGED_READ),
+            Err(Fault::Translation { level: 1 })
+        );
+    }
+
+    #[test]
+    fn mpu_regions() {
+        use crate::pmsav8::{validate::ValidationError, El1Config};
+
+        const REGIONS: [crate::pmsav8::El1Region; 3] = MAP.el1_regions();
+        let config = El1Config {
+            background_config: false,
+            regions: &REGIONS,
+            memory_attributes: MAP.pmsav8_attributes(),
+        };
+        assert_eq!(config.validate(), Ok::<(), ValidationError>(()));
+        assert_eq!(REGIONS[2].mair, 1);
+        assert_eq!(
+            *REGIONS[0].range.end() as usize,
+            0x003F_FFFF,
+            "end is inclusive"
+        );
+
+        const V7_REGIONS: [crate::pmsav7::Region; 3] = MAP.pmsav7_regions();
+        assert_eq!(V7_REGIONS[0].size, crate::pmsav7::RegionSize::_4M);
+        assert_eq!(V7_REGIONS[1].size, crate::pmsav7::RegionSize::_1M);
+    }
+
+    #[test]
+    fn attributes() {
+        use crate::memory::Shareability;
+
+        const V7_REGIONS: [crate::pmsav7::Region; 3] = MAP.pmsav7_regions();
+        const REGIONS: [crate::pmsav8::El1Region; 3] = MAP.el1_regions();
+        for i in 0..3 {
+            let expected = MAP.ranges[i].kind.attributes();
+            assert_eq!(
+                Attributes::from_pmsav7(&V7_REGIONS[i].mem_attr),
+                Ok(expected)
+            );
+            let mem_attr = &MAP.pmsav8_attributes()[REGIONS[i].mair as usize];
+            assert_eq!(
+                Attributes::from_pmsav8(mem_attr, Shareability::NonShareable),
+                expected
+            );
+        }
+        assert_eq!(
+            REGIONS[2].shareability,
+            crate::pmsav8::El1Shareability::OuterShareable
+        );
+    }
+}
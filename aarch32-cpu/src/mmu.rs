@@ -1,6 +1,23 @@
 //! Code and types for use with MMU programming on a VMSA (Virtual Memory System Architecture) platform
 
-use arbitrary_int::{u12, u2, u3, u4};
+use arbitrary_int::{prelude::*, u12, u2, u20, u22, u3, u4, u5};
+
+use crate::register::{
+    dacr::DomainAccess,
+    dfsr::DfsrStatus,
+    par::{ParInnerAttributes, ParOuterAttributes},
+    ttbr0::Region,
+    Dacr, Nmrr, Par, Prrr, RemapMemoryType, Sctlr, Ttbcr, Ttbr0,
+};
+
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod address_space;
+pub mod domain;
+pub mod evaluate;
+pub mod lpae;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod mapper;
+pub mod walker;
 
 /// Number of 1 MiB pages in a 4 GiB virtual address space
 pub const NUM_L1_PAGE_TABLE_ENTRIES: usize = 4096;
@@ -16,6 +33,59 @@ pub struct L1Table {
 /// This type is thread-safe
 unsafe impl Sync for L1Table {}
 
+/// A first level translation table which TTBR0 can point at
+///
+/// With TTBCR.N set to zero this is a full [`L1Table`]. Larger values of N
+/// shrink the part of the address space translated by TTBR0, and so the size
+/// of its table, with TTBR1 translating the rest.
+pub trait Ttbr0Table: Sync {
+    /// The value of TTBCR.N which selects this size of table
+    const N: u8;
+    /// The number of 1 MiB entries in this table
+    const NUM_ENTRIES: usize = NUM_L1_PAGE_TABLE_ENTRIES >> Self::N;
+
+    /// Get a pointer to the first entry in the table
+    fn as_ptr(&self) -> *mut L1Section;
+}
+
+impl Ttbr0Table for L1Table {
+    const N: u8 = 0;
+
+    fn as_ptr(&self) -> *mut L1Section {
+        self.entries.get() as *mut L1Section
+    }
+}
+
+macro_rules! split_l1_table {
+    ($name:ident, $n:literal, $align:literal, $size:literal) => {
+        #[doc = concat!("Holds a TTBR0 translation table for TTBCR.N = ", $n, ", covering the bottom ", $size, " of the address space")]
+        #[repr(C, align($align))]
+        pub struct $name {
+            pub entries:
+                core::cell::UnsafeCell<[L1Section; NUM_L1_PAGE_TABLE_ENTRIES >> $n]>,
+        }
+
+        /// This type is thread-safe
+        unsafe impl Sync for $name {}
+
+        impl Ttbr0Table for $name {
+            const N: u8 = $n;
+
+            fn as_ptr(&self) -> *mut L1Section {
+                self.entries.get() as *mut L1Section
+            }
+        }
+    };
+}
+
+split_l1_table!(L1TableN1, 1, 8192, "2 GiB");
+split_l1_table!(L1TableN2, 2, 4096, "1 GiB");
+split_l1_table!(L1TableN3, 3, 2048, "512 MiB");
+split_l1_table!(L1TableN4, 4, 1024, "256 MiB");
+split_l1_table!(L1TableN5, 5, 512, "128 MiB");
+split_l1_table!(L1TableN6, 6, 256, "64 MiB");
+split_l1_table!(L1TableN7, 7, 128, "32 MiB");
+
 /// Represents an invalid L1 Entry
 #[derive(Debug, thiserror::Error)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -23,6 +93,19 @@ unsafe impl Sync for L1Table {}
 #[error("invalid L1 entry type {0:?}")]
 pub struct InvalidL1EntryType(pub L1EntryType);
 
+/// Represents an L1 Entry which is not a supersection
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvalidSupersection {
+    /// The entry is not a section at all
+    #[error(transparent)]
+    EntryType(#[from] InvalidL1EntryType),
+    /// The entry is a section, but not a supersection
+    #[error("L1 section entry is not a supersection")]
+    NotSupersection,
+}
+
 /// Access permissions for a region of memory
 #[bitbybit::bitenum(u3, exhaustive = true)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -99,6 +182,14 @@ impl MemoryRegionAttributesRaw {
             b,
         }
     }
+
+    /// The index into a [`TexRemap`] selected by these bits, when `SCTLR.TRE` is set
+    #[inline]
+    pub const fn remap_index(&self) -> u3 {
+        u3::new(
+            ((self.type_extensions.value() & 0b1) << 2) | ((self.c as u8) << 1) | (self.b as u8),
+        )
+    }
 }
 
 /// Whether/how a region is cacheable
@@ -129,6 +220,13 @@ pub enum MemoryRegionAttributes {
         inner: CacheableMemoryAttribute,
         outer: CacheableMemoryAttribute,
     },
+    /// One of the eight memory types in a [`TexRemap`]
+    ///
+    /// Only meaningful when `SCTLR.TRE` is set. The index is stored in
+    /// `TEX[0]`, C and B, leaving `TEX[2:1]` clear for use by software.
+    Remapped {
+        index: u3,
+    },
 }
 
 impl MemoryRegionAttributes {
@@ -162,6 +260,140 @@ impl MemoryRegionAttributes {
                     (*inner as u8 & 0b01) != 0,
                 )
             }
+            MemoryRegionAttributes::Remapped { index } => {
+                let index = index.value();
+                MemoryRegionAttributesRaw::new(
+                    u3::new(index >> 2),
+                    (index & 0b10) != 0,
+                    (index & 0b01) != 0,
+                )
+            }
+        }
+    }
+}
+
+/// A memory type for one of the eight regions of a [`TexRemap`]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RemapRegion {
+    StronglyOrdered,
+    Device,
+    Normal {
+        inner: CacheableMemoryAttribute,
+        outer: CacheableMemoryAttribute,
+        /// Whether shareable memory of this type is Outer Shareable (rather
+        /// than only Inner Shareable)
+        outer_shareable: bool,
+    },
+}
+
+/// A TEX remap configuration, as programmed into PRRR and NMRR
+///
+/// When `SCTLR.TRE` is set, the `TEX[0]`, C and B bits of a descriptor form an
+/// index into these eight regions. Use [`MemoryRegionAttributes::Remapped`] to
+/// build descriptors which refer to them.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TexRemap {
+    /// The memory type of each region
+    pub regions: [RemapRegion; 8],
+    /// Whether Device memory is shareable when the S bit is 0 and 1 respectively
+    pub device_shareable: [bool; 2],
+    /// Whether Normal memory is shareable when the S bit is 0 and 1 respectively
+    pub normal_shareable: [bool; 2],
+}
+
+impl TexRemap {
+    /// The configuration used by Linux for short-descriptor tables
+    ///
+    /// | Index | Memory type                          |
+    /// |-------|--------------------------------------|
+    /// | 0     | Strongly-ordered                     |
+    /// | 1     | Normal, Non-cacheable                |
+    /// | 2     | Normal, Write-Through                |
+    /// | 3     | Normal, Write-Back no Write-Allocate |
+    /// | 4     | Device                               |
+    /// | 7     | Normal, Write-Back Write-Allocate    |
+    pub const LINUX: TexRemap = TexRemap {
+        regions: [
+            RemapRegion::StronglyOrdered,
+            RemapRegion::Normal {
+                inner: CacheableMemoryAttribute::NonCacheable,
+                outer: CacheableMemoryAttribute::NonCacheable,
+                outer_shareable: false,
+            },
+            RemapRegion::Normal {
+                inner: CacheableMemoryAttribute::WriteThroughNoWriteAlloc,
+                outer: CacheableMemoryAttribute::WriteThroughNoWriteAlloc,
+                outer_shareable: false,
+            },
+            RemapRegion::Normal {
+                inner: CacheableMemoryAttribute::WriteBackNoWriteAlloc,
+                outer: CacheableMemoryAttribute::WriteBackNoWriteAlloc,
+                outer_shareable: false,
+            },
+            RemapRegion::Device,
+            RemapRegion::StronglyOrdered,
+            RemapRegion::StronglyOrdered,
+            RemapRegion::Normal {
+                inner: CacheableMemoryAttribute::WriteBackWriteAlloc,
+                outer: CacheableMemoryAttribute::WriteBackWriteAlloc,
+                outer_shareable: false,
+            },
+        ],
+        device_shareable: [false, true],
+        normal_shareable: [false, true],
+    };
+
+    /// Get the value for PRRR
+    pub const fn prrr(&self) -> Prrr {
+        let mut prrr = Prrr::DEFAULT
+            .with_ds0(self.device_shareable[0])
+            .with_ds1(self.device_shareable[1])
+            .with_ns0(self.normal_shareable[0])
+            .with_ns1(self.normal_shareable[1]);
+        let mut i = 0;
+        while i < self.regions.len() {
+            let (memory_type, nos) = match self.regions[i] {
+                RemapRegion::StronglyOrdered => (RemapMemoryType::StronglyOrdered, true),
+                RemapRegion::Device => (RemapMemoryType::Device, true),
+                RemapRegion::Normal {
+                    outer_shareable, ..
+                } => (RemapMemoryType::Normal, !outer_shareable),
+            };
+            prrr = prrr.with_tr(i, memory_type).with_nos(i, nos);
+            i += 1;
+        }
+        prrr
+    }
+
+    /// Get the value for NMRR
+    pub const fn nmrr(&self) -> Nmrr {
+        let mut nmrr = Nmrr::DEFAULT;
+        let mut i = 0;
+        while i < self.regions.len() {
+            if let RemapRegion::Normal { inner, outer, .. } = self.regions[i] {
+                nmrr = nmrr.with_ir(i, inner).with_or(i, outer);
+            }
+            i += 1;
+        }
+        nmrr
+    }
+
+    /// Write this configuration to PRRR and NMRR
+    ///
+    /// This does not set `SCTLR.TRE`.
+    ///
+    /// # Safety
+    ///
+    /// If the TEX remap is enabled, changing the configuration changes the
+    /// meaning of every descriptor in the active translation tables.
+    pub unsafe fn write(&self) {
+        unsafe {
+            Prrr::write(self.prrr());
+            Nmrr::write(self.nmrr());
         }
     }
 }
@@ -340,9 +572,847 @@ impl L1Section {
     }
 }
 
+/// 16 MiB supersection translation entry, mapping a 16 MiB region to a physical address.
+///
+/// Supersections can map physical addresses up to 40 bits wide. They must be
+/// written to 16 consecutive entries of an [`L1Table`], which
+/// [`Supersection::write_to`] does for you. Supersections are always in domain 0.
+///
+/// The ARMv7-A and ArmV7-R architecture reference manual p.1326 specifies these attributes in
+/// more detail.
+#[bitbybit::bitfield(u32, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq)]
+pub struct Supersection {
+    /// Physical address bits `[31:24]`.
+    #[bits(24..=31, rw)]
+    base_addr_upper_bits: u8,
+    /// Physical address bits `[35:32]`.
+    #[bits(20..=23, rw)]
+    extended_base_addr_low: u4,
+    /// Always set for a supersection.
+    #[bit(18, rw)]
+    supersection: bool,
+    /// Non-global bit.
+    #[bit(17, rw)]
+    ng: bool,
+    /// Shareable bit.
+    #[bit(16, rw)]
+    s: bool,
+    #[bit(15, rw)]
+    apx: bool,
+    /// Type extension bits.
+    #[bits(12..=14, rw)]
+    tex: u3,
+    #[bits(10..=11, rw)]
+    ap: u2,
+    #[bit(9, rw)]
+    p_bit: bool,
+    /// Physical address bits `[39:36]`.
+    #[bits(5..=8, rw)]
+    extended_base_addr_high: u4,
+    #[bit(4, rw)]
+    xn: bool,
+    #[bit(3, rw)]
+    c: bool,
+    #[bit(2, rw)]
+    b: bool,
+    #[bits(0..=1, rw)]
+    entry_type: L1EntryType,
+}
+
+impl core::fmt::Debug for Supersection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Supersection {{ base_addr={:#x} ng={} s={} apx={} tex={:#b} ap={:#b} xn={} c={} b={} }}",
+            self.phys_addr(),
+            self.ng() as u8,
+            self.s() as u8,
+            self.apx() as u8,
+            self.tex(),
+            self.ap(),
+            self.xn() as u8,
+            self.c() as u8,
+            self.b() as u8,
+        )
+    }
+}
+
+impl Supersection {
+    /// Number of L1 table entries covered by a supersection.
+    pub const NUM_ENTRIES: usize = 16;
+
+    /// Size of a supersection in bytes.
+    pub const SIZE: u32 = 16 * 1024 * 1024;
+
+    /// Generates a new supersection from a (up to 40-bit) physical address and section attributes.
+    ///
+    /// # Panics
+    ///
+    /// * Physical address not aligned to 16 MiB.
+    /// * Physical address wider than 40 bits.
+    /// * The domain in `section_attrs` is not 0.
+    pub const fn new_with_addr_and_attrs(phys_addr: u64, section_attrs: SectionAttributes) -> Self {
+        if phys_addr & (Self::SIZE as u64 - 1) != 0 {
+            panic!("physical base address for supersection must be aligned to 16 MiB");
+        }
+        if phys_addr >> 40 != 0 {
+            panic!("physical base address for supersection must fit in 40 bits");
+        }
+        if section_attrs.domain.value() != 0 {
+            panic!("supersections are always in domain 0");
+        }
+        Supersection::builder()
+            .with_base_addr_upper_bits((phys_addr >> 24) as u8)
+            .with_extended_base_addr_low(u4::new(((phys_addr >> 32) & 0xF) as u8))
+            .with_supersection(true)
+            .with_ng(section_attrs.non_global)
+            .with_s(section_attrs.shareable)
+            .with_apx(section_attrs.access.apx())
+            .with_tex(section_attrs.memory_attrs.type_extensions)
+            .with_ap(section_attrs.access.ap())
+            .with_p_bit(section_attrs.p_bit)
+            .with_extended_base_addr_high(u4::new(((phys_addr >> 36) & 0xF) as u8))
+            .with_xn(section_attrs.execute_never)
+            .with_c(section_attrs.memory_attrs.c)
+            .with_b(section_attrs.memory_attrs.b)
+            .with_entry_type(L1EntryType::Section)
+            .build()
+    }
+
+    /// The physical address this supersection maps to.
+    #[inline]
+    pub const fn phys_addr(&self) -> u64 {
+        ((self.extended_base_addr_high().value() as u64) << 36)
+            | ((self.extended_base_addr_low().value() as u64) << 32)
+            | ((self.base_addr_upper_bits() as u64) << 24)
+    }
+
+    /// Retrieve the section attributes. The domain is always 0.
+    #[inline]
+    pub const fn section_attrs(&self) -> SectionAttributes {
+        SectionAttributes {
+            non_global: self.ng(),
+            p_bit: self.p_bit(),
+            shareable: self.s(),
+            access: AccessPermissions::new(self.apx(), self.ap()),
+            memory_attrs: MemoryRegionAttributesRaw::new(self.tex(), self.c(), self.b()),
+            domain: u4::new(0),
+            execute_never: self.xn(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in an [`L1Table`].
+    ///
+    /// Remember that this entry must be replicated [`Supersection::NUM_ENTRIES`] times.
+    #[inline]
+    pub const fn as_l1_entry(&self) -> L1Section {
+        L1Section::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret an [`L1Table`] entry as a supersection.
+    #[inline]
+    pub fn from_l1_entry(entry: L1Section) -> Result<Self, InvalidSupersection> {
+        let entry_type = L1EntryType::new_with_raw_value(u2::new((entry.raw_value() & 0b11) as u8));
+        if entry_type != L1EntryType::Section {
+            return Err(InvalidL1EntryType(entry_type).into());
+        }
+        if entry.raw_value() & (1 << 18) == 0 {
+            return Err(InvalidSupersection::NotSupersection);
+        }
+        Ok(Self::new_with_raw_value(entry.raw_value()))
+    }
+
+    /// Write this supersection into all 16 of the L1 table entries that cover `virt_addr`.
+    ///
+    /// # Panics
+    ///
+    /// Virtual address not aligned to 16 MiB.
+    pub const fn write_to(
+        &self,
+        entries: &mut [L1Section; NUM_L1_PAGE_TABLE_ENTRIES],
+        virt_addr: u32,
+    ) {
+        if virt_addr & (Self::SIZE - 1) != 0 {
+            panic!("virtual base address for supersection must be aligned to 16 MiB");
+        }
+        let first = (virt_addr >> 20) as usize;
+        let mut i = 0;
+        while i < Self::NUM_ENTRIES {
+            entries[first + i] = self.as_l1_entry();
+            i += 1;
+        }
+    }
+}
+
+/// Number of 4 KiB pages in the 1 MiB covered by an L2 page table
+pub const NUM_L2_PAGE_TABLE_ENTRIES: usize = 256;
+
+/// Holds an L2 (coarse) page table with appropriate alignment
+///
+/// An L2 table maps 1 MiB of virtual address space in 4 KiB or 64 KiB pages. It
+/// is referenced from an [`L1Table`] by an [`L1PageTable`] entry.
+#[repr(C, align(1024))]
+pub struct L2Table {
+    pub entries: core::cell::UnsafeCell<[L2Entry; NUM_L2_PAGE_TABLE_ENTRIES]>,
+}
+
+/// This type is thread-safe
+unsafe impl Sync for L2Table {}
+
+/// L1 translation entry pointing to an L2 page table.
+///
+/// The ARMv7-A and ArmV7-R architecture reference manual p.1326 specifies these fields in more
+/// detail.
+#[bitbybit::bitfield(u32, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq)]
+pub struct L1PageTable {
+    /// Upper 22 bits of the L2 page table base address.
+    #[bits(10..=31, rw)]
+    base_addr_upper_bits: u22,
+    /// Implementation defined bit.
+    #[bit(9, rw)]
+    p_bit: bool,
+    #[bits(5..=8, rw)]
+    domain: u4,
+    /// Non-secure bit.
+    #[bit(3, rw)]
+    ns: bool,
+    /// Privileged execute-never bit.
+    #[bit(2, rw)]
+    pxn: bool,
+    #[bits(0..=1, rw)]
+    entry_type: L1EntryType,
+}
+
+impl core::fmt::Debug for L1PageTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "L1PageTable {{ base_addr={:#x} p={} domain={:#b} ns={} pxn={} }}",
+            self.l2_table_addr(),
+            self.p_bit() as u8,
+            self.domain(),
+            self.ns() as u8,
+            self.pxn() as u8,
+        )
+    }
+}
+
+impl L1PageTable {
+    /// Generates a new L1 entry pointing to the L2 table at the given physical address.
+    ///
+    /// # Panics
+    ///
+    /// L2 table address not aligned to 1 KiB.
+    pub const fn new_with_addr(l2_table_addr: u32, domain: u4, ns: bool, pxn: bool) -> Self {
+        if l2_table_addr & 0x3FF != 0 {
+            panic!("L2 page table address must be aligned to 1 KiB");
+        }
+        L1PageTable::builder()
+            .with_base_addr_upper_bits(u22::new(l2_table_addr >> 10))
+            .with_p_bit(false)
+            .with_domain(domain)
+            .with_ns(ns)
+            .with_pxn(pxn)
+            .with_entry_type(L1EntryType::PageTable)
+            .build()
+    }
+
+    /// The physical address of the L2 table this entry points to.
+    #[inline]
+    pub const fn l2_table_addr(&self) -> u32 {
+        self.base_addr_upper_bits().value() << 10
+    }
+
+    /// Convert to an entry which can be stored in an [`L1Table`].
+    #[inline]
+    pub const fn as_l1_entry(&self) -> L1Section {
+        L1Section::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret an [`L1Table`] entry as a page table pointer.
+    #[inline]
+    pub fn from_l1_entry(entry: L1Section) -> Result<Self, InvalidL1EntryType> {
+        let entry_type = L1EntryType::new_with_raw_value(u2::new((entry.raw_value() & 0b11) as u8));
+        if entry_type != L1EntryType::PageTable {
+            return Err(InvalidL1EntryType(entry_type));
+        }
+        Ok(Self::new_with_raw_value(entry.raw_value()))
+    }
+}
+
+/// The type of an L2 Entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum L2EntryType {
+    /// Access generates an abort exception. Indicates an unmapped virtual address.
+    Fault,
+    /// Maps a 64 KiB region to a physical address. Requires 16 entries in the L2 table.
+    LargePage,
+    /// Maps a 4 KiB region to a physical address.
+    SmallPage,
+}
+
+/// Represents an invalid L2 Entry
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("invalid L2 entry type {0:?}")]
+pub struct InvalidL2EntryType(pub L2EntryType);
+
+/// Individual page attributes for an L2 small or large page.
+///
+/// Unlike [`SectionAttributes`], there is no domain here; that comes from the
+/// [`L1PageTable`] entry which points to the L2 table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageAttributes {
+    /// NG bit
+    pub non_global: bool,
+    pub shareable: bool,
+    /// AP bits
+    pub access: AccessPermissions,
+    pub memory_attrs: MemoryRegionAttributesRaw,
+    /// xN bit.
+    pub execute_never: bool,
+}
+
+/// A raw entry in an [`L2Table`]
+///
+/// Use [`SmallPage`] or [`LargePage`] to construct or decode these.
+#[bitbybit::bitfield(u32, default = 0, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct L2Entry {
+    #[bits(0..=1, r)]
+    type_bits: u2,
+}
+
+impl L2Entry {
+    /// The type of this entry
+    #[inline]
+    pub const fn entry_type(&self) -> L2EntryType {
+        match self.type_bits().value() {
+            0b00 => L2EntryType::Fault,
+            0b01 => L2EntryType::LargePage,
+            _ => L2EntryType::SmallPage,
+        }
+    }
+}
+
+/// 4 KiB small page translation entry, mapping a 4 KiB region to a physical address.
+///
+/// The ARMv7-A and ArmV7-R architecture reference manual p.1327 specifies these attributes in
+/// more detail.
+#[bitbybit::bitfield(u32, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq)]
+pub struct SmallPage {
+    /// Page base address upper bits.
+    #[bits(12..=31, rw)]
+    base_addr_upper_bits: u20,
+    /// Non-global bit.
+    #[bit(11, rw)]
+    ng: bool,
+    /// Shareable bit.
+    #[bit(10, rw)]
+    s: bool,
+    #[bit(9, rw)]
+    apx: bool,
+    /// Type extension bits.
+    #[bits(6..=8, rw)]
+    tex: u3,
+    #[bits(4..=5, rw)]
+    ap: u2,
+    #[bit(3, rw)]
+    c: bool,
+    #[bit(2, rw)]
+    b: bool,
+    /// Always set for a small page.
+    #[bit(1, rw)]
+    small_page: bool,
+    #[bit(0, rw)]
+    xn: bool,
+}
+
+impl core::fmt::Debug for SmallPage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "SmallPage {{ base_addr={:#x} ng={} s={} apx={} tex={:#b} ap={:#b} xn={} c={} b={} }}",
+            self.base_addr_upper_bits(),
+            self.ng() as u8,
+            self.s() as u8,
+            self.apx() as u8,
+            self.tex(),
+            self.ap(),
+            self.xn() as u8,
+            self.c() as u8,
+            self.b() as u8,
+        )
+    }
+}
+
+impl SmallPage {
+    /// Generates a new small page from a physical address and page attributes.
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 4 KiB.
+    pub const fn new_with_addr_and_attrs(phys_addr: u32, page_attrs: PageAttributes) -> Self {
+        if phys_addr & 0xFFF != 0 {
+            panic!("physical base address for small page must be aligned to 4 KiB");
+        }
+        SmallPage::builder()
+            .with_base_addr_upper_bits(u20::new(phys_addr >> 12))
+            .with_ng(page_attrs.non_global)
+            .with_s(page_attrs.shareable)
+            .with_apx(page_attrs.access.apx())
+            .with_tex(page_attrs.memory_attrs.type_extensions)
+            .with_ap(page_attrs.access.ap())
+            .with_c(page_attrs.memory_attrs.c)
+            .with_b(page_attrs.memory_attrs.b)
+            .with_small_page(true)
+            .with_xn(page_attrs.execute_never)
+            .build()
+    }
+
+    /// The physical address this page maps to.
+    #[inline]
+    pub const fn phys_addr(&self) -> u32 {
+        self.base_addr_upper_bits().value() << 12
+    }
+
+    /// Retrieve the page attributes.
+    #[inline]
+    pub const fn page_attrs(&self) -> PageAttributes {
+        PageAttributes {
+            non_global: self.ng(),
+            shareable: self.s(),
+            access: AccessPermissions::new(self.apx(), self.ap()),
+            memory_attrs: MemoryRegionAttributesRaw::new(self.tex(), self.c(), self.b()),
+            execute_never: self.xn(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in an [`L2Table`].
+    #[inline]
+    pub const fn as_l2_entry(&self) -> L2Entry {
+        L2Entry::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret an [`L2Table`] entry as a small page.
+    #[inline]
+    pub fn from_l2_entry(entry: L2Entry) -> Result<Self, InvalidL2EntryType> {
+        match entry.entry_type() {
+            L2EntryType::SmallPage => Ok(Self::new_with_raw_value(entry.raw_value())),
+            other => Err(InvalidL2EntryType(other)),
+        }
+    }
+}
+
+/// 64 KiB large page translation entry, mapping a 64 KiB region to a physical address.
+///
+/// A large page must be written to 16 consecutive entries of an [`L2Table`],
+/// starting at an index which is a multiple of 16.
+///
+/// The ARMv7-A and ArmV7-R architecture reference manual p.1327 specifies these attributes in
+/// more detail.
+#[bitbybit::bitfield(u32, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq)]
+pub struct LargePage {
+    /// Page base address upper bits.
+    #[bits(16..=31, rw)]
+    base_addr_upper_bits: u16,
+    #[bit(15, rw)]
+    xn: bool,
+    /// Type extension bits.
+    #[bits(12..=14, rw)]
+    tex: u3,
+    /// Non-global bit.
+    #[bit(11, rw)]
+    ng: bool,
+    /// Shareable bit.
+    #[bit(10, rw)]
+    s: bool,
+    #[bit(9, rw)]
+    apx: bool,
+    #[bits(4..=5, rw)]
+    ap: u2,
+    #[bit(3, rw)]
+    c: bool,
+    #[bit(2, rw)]
+    b: bool,
+    #[bits(0..=1, rw)]
+    type_bits: u2,
+}
+
+impl core::fmt::Debug for LargePage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "LargePage {{ base_addr={:#x} ng={} s={} apx={} tex={:#b} ap={:#b} xn={} c={} b={} }}",
+            self.base_addr_upper_bits(),
+            self.ng() as u8,
+            self.s() as u8,
+            self.apx() as u8,
+            self.tex(),
+            self.ap(),
+            self.xn() as u8,
+            self.c() as u8,
+            self.b() as u8,
+        )
+    }
+}
+
+impl LargePage {
+    /// Number of L2 table entries covered by a large page.
+    pub const NUM_ENTRIES: usize = 16;
+
+    /// Generates a new large page from a physical address and page attributes.
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 64 KiB.
+    pub const fn new_with_addr_and_attrs(phys_addr: u32, page_attrs: PageAttributes) -> Self {
+        if phys_addr & 0xFFFF != 0 {
+            panic!("physical base address for large page must be aligned to 64 KiB");
+        }
+        LargePage::builder()
+            .with_base_addr_upper_bits((phys_addr >> 16) as u16)
+            .with_xn(page_attrs.execute_never)
+            .with_tex(page_attrs.memory_attrs.type_extensions)
+            .with_ng(page_attrs.non_global)
+            .with_s(page_attrs.shareable)
+            .with_apx(page_attrs.access.apx())
+            .with_ap(page_attrs.access.ap())
+            .with_c(page_attrs.memory_attrs.c)
+            .with_b(page_attrs.memory_attrs.b)
+            .with_type_bits(u2::new(0b01))
+            .build()
+    }
+
+    /// The physical address this page maps to.
+    #[inline]
+    pub const fn phys_addr(&self) -> u32 {
+        (self.base_addr_upper_bits() as u32) << 16
+    }
+
+    /// Retrieve the page attributes.
+    #[inline]
+    pub const fn page_attrs(&self) -> PageAttributes {
+        PageAttributes {
+            non_global: self.ng(),
+            shareable: self.s(),
+            access: AccessPermissions::new(self.apx(), self.ap()),
+            memory_attrs: MemoryRegionAttributesRaw::new(self.tex(), self.c(), self.b()),
+            execute_never: self.xn(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in an [`L2Table`].
+    ///
+    /// Remember that this entry must be replicated [`LargePage::NUM_ENTRIES`] times.
+    #[inline]
+    pub const fn as_l2_entry(&self) -> L2Entry {
+        L2Entry::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret an [`L2Table`] entry as a large page.
+    #[inline]
+    pub fn from_l2_entry(entry: L2Entry) -> Result<Self, InvalidL2EntryType> {
+        match entry.entry_type() {
+            L2EntryType::LargePage => Ok(Self::new_with_raw_value(entry.raw_value())),
+            other => Err(InvalidL2EntryType(other)),
+        }
+    }
+}
+
+/// The kind of access to check with [`translate`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccessKind {
+    /// Stage 1 translation of a PL1 read, in the current Security state (ATS1CPR)
+    PrivilegedRead,
+    /// Stage 1 translation of a PL1 write, in the current Security state (ATS1CPW)
+    PrivilegedWrite,
+    /// Stage 1 translation of a PL0 read, in the current Security state (ATS1CUR)
+    UserRead,
+    /// Stage 1 translation of a PL0 write, in the current Security state (ATS1CUW)
+    UserWrite,
+    /// Stage 1 and 2 translation of a Non-secure PL1 read (ATS12NSOPR)
+    NonSecurePrivilegedRead,
+    /// Stage 1 and 2 translation of a Non-secure PL1 write (ATS12NSOPW)
+    NonSecurePrivilegedWrite,
+    /// Stage 1 and 2 translation of a Non-secure PL0 read (ATS12NSOUR)
+    NonSecureUserRead,
+    /// Stage 1 and 2 translation of a Non-secure PL0 write (ATS12NSOUW)
+    NonSecureUserWrite,
+}
+
+/// A successful address translation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysTranslation {
+    /// The physical address (up to 40 bits, if a Supersection was used)
+    pub physical_address: u64,
+    /// Inner memory attributes
+    pub inner: ParInnerAttributes,
+    /// Outer memory attributes
+    pub outer: ParOuterAttributes,
+    /// Is the memory Shareable?
+    pub shareable: bool,
+    /// Is the memory Outer Shareable (as opposed to Inner Shareable)?
+    pub outer_shareable: bool,
+    /// Is the physical address in the Non-secure address space?
+    pub non_secure: bool,
+    /// Did the translation use a Supersection?
+    pub supersection: bool,
+}
+
+/// A failed address translation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TranslationFault {
+    /// Fault status, in the DFSR encoding
+    pub status_raw: u5,
+    /// External abort qualifier
+    pub external: bool,
+}
+
+impl TranslationFault {
+    /// Decode the fault status
+    pub fn status(&self) -> Result<DfsrStatus, u8> {
+        let status = self.status_raw.as_u8();
+        DfsrStatus::try_from(status).map_err(|_| status)
+    }
+}
+
+impl PhysTranslation {
+    /// Decode a (Short-descriptor format) PAR value, as produced by translating `va`
+    pub const fn from_par(par: Par, va: u32) -> Result<PhysTranslation, TranslationFault> {
+        if par.f() {
+            return Err(TranslationFault {
+                status_raw: par.fault_status_raw(),
+                external: par.fault_ext(),
+            });
+        }
+        let raw = par.raw_value();
+        let physical_address = if par.ss() {
+            let high = ((raw >> 16) & 0xFF) as u64;
+            (high << 32) | (raw & 0xFF00_0000) as u64 | (va & 0x00FF_FFFF) as u64
+        } else {
+            (raw & 0xFFFF_F000) as u64 | (va & 0xFFF) as u64
+        };
+        Ok(PhysTranslation {
+            physical_address,
+            inner: par.inner(),
+            outer: par.outer(),
+            shareable: par.sh(),
+            outer_shareable: par.sh() && !par.nos(),
+            non_secure: par.ns(),
+            supersection: par.ss(),
+        })
+    }
+}
+
+/// Ask the MMU to translate a virtual address, as if it were accessed in the given way
+///
+/// Issues the relevant ATS1Cxx or ATS12NSOxx operation, waits for it with an
+/// ISB and decodes the result from PAR. Interrupts are disabled for the
+/// duration, so that an interrupt handler cannot clobber PAR.
+///
+/// Only the Short-descriptor translation table format is supported.
+#[cfg(any(doc, arm_architecture = "v7-a"))]
+pub fn translate(va: usize, kind: AccessKind) -> Result<PhysTranslation, TranslationFault> {
+    use crate::register::{
+        Ats12nsopr, Ats12nsopw, Ats12nsour, Ats12nsouw, Ats1cpr, Ats1cpw, Ats1cur, Ats1cuw,
+    };
+    let va = va as u32;
+    let par = crate::interrupt::free(|| {
+        match kind {
+            AccessKind::PrivilegedRead => Ats1cpr::write(va),
+            AccessKind::PrivilegedWrite => Ats1cpw::write(va),
+            AccessKind::UserRead => Ats1cur::write(va),
+            AccessKind::UserWrite => Ats1cuw::write(va),
+            AccessKind::NonSecurePrivilegedRead => Ats12nsopr::write(va),
+            AccessKind::NonSecurePrivilegedWrite => Ats12nsopw::write(va),
+            AccessKind::NonSecureUserRead => Ats12nsour::write(va),
+            AccessKind::NonSecureUserWrite => Ats12nsouw::write(va),
+        }
+        crate::asm::isb();
+        Par::read()
+    });
+    PhysTranslation::from_par(par, va)
+}
+
+/// How [`enable`] should set up the MMU and caches
+#[derive(Debug, Copy, Clone)]
+pub struct MmuConfig {
+    /// Table walk attributes for TTBR0 and TTBR1 (the table addresses are
+    /// filled in by [`enable`])
+    pub ttbr0: Ttbr0,
+    /// Translation table control
+    ///
+    /// [`enable`] sets TTBCR.N and PD1 to match the tables it is given, see
+    /// [`MmuConfig::ttbcr`].
+    pub ttbcr: Ttbcr,
+    /// Access to each of the 16 domains
+    pub dacr: Dacr,
+    /// Enable the data and unified caches (SCTLR.C)
+    pub data_cache: bool,
+    /// Enable the instruction cache (SCTLR.I)
+    pub instruction_cache: bool,
+    /// Enable branch prediction (SCTLR.Z)
+    pub branch_prediction: bool,
+}
+
+impl MmuConfig {
+    /// Write-Back Write-Allocate table walks, Client access to every domain,
+    /// and all the caches on
+    pub const DEFAULT: MmuConfig = MmuConfig {
+        ttbr0: Ttbr0::new_with_raw_value(0)
+            .with_irgn(true)
+            .with_c(false)
+            .with_rgn(Region::WriteBackWriteAllocateCacheable),
+        ttbcr: Ttbcr::DEFAULT,
+        dacr: Dacr::new_with_all(DomainAccess::Client),
+        data_cache: true,
+        instruction_cache: true,
+        branch_prediction: true,
+    };
+
+    /// The TTBCR value to use with a TTBR0 table for TTBCR.N = `n`
+    ///
+    /// Without a TTBR1 table, walks through TTBR1 are disabled (PD1), so
+    /// addresses above the TTBR0 region give translation faults.
+    pub const fn ttbcr(&self, n: u3, has_ttbr1: bool) -> Ttbcr {
+        self.ttbcr.with_n(n).with_pd1(!has_ttbr1)
+    }
+
+    /// Apply the cache and MMU enables to an SCTLR value
+    pub const fn sctlr(&self, sctlr: Sctlr) -> Sctlr {
+        sctlr
+            .with_m(true)
+            .with_c(self.data_cache)
+            .with_i(self.instruction_cache)
+            .with_z(self.branch_prediction)
+    }
+}
+
+impl Default for MmuConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Turn on the MMU, using the given translation tables
+///
+/// Invalidates the instruction cache, TLB and branch predictor, programs
+/// TTBCR, TTBR0, TTBR1 and DACR, and then sets SCTLR.M along with the cache
+/// enables from `config`.
+///
+/// TTBCR.N comes from the type of `ttbr0`. If it is non-zero, `ttbr1`
+/// translates the addresses above the TTBR0 region; without a `ttbr1` table
+/// those addresses fault.
+///
+/// # Safety
+///
+/// The tables must map the currently executing code, stack and data at their
+/// current (i.e. physical) addresses, and the data cache must not hold stale
+/// contents for memory which the tables mark as cacheable.
+#[cfg(any(doc, arm_architecture = "v7-a"))]
+pub unsafe fn enable<T: Ttbr0Table>(
+    ttbr0: &'static T,
+    ttbr1: Option<&'static L1Table>,
+    config: MmuConfig,
+) {
+    use crate::register::{BpIAll, Iciallu, TlbIAll, Ttbr1};
+    Iciallu::write();
+    TlbIAll::write();
+    BpIAll::write();
+    crate::asm::dsb();
+    unsafe {
+        Ttbcr::write(config.ttbcr(u3::new(T::N), ttbr1.is_some()));
+        Ttbr0::write(config.ttbr0.with_address(ttbr0.as_ptr() as usize));
+        if let Some(table) = ttbr1 {
+            // TTBR1 has the same walk attribute bits as TTBR0
+            let mut value = Ttbr1::new_with_raw_value(config.ttbr0.raw_value() & 0x7F);
+            value.set_address(table.as_ptr() as usize);
+            Ttbr1::write(value);
+        }
+    }
+    Dacr::write(config.dacr);
+    crate::asm::isb();
+    // This does the DSB and ISB needed after changing SCTLR
+    Sctlr::modify(|s| *s = config.sctlr(*s));
+}
+
+/// Turn off the MMU and the caches
+///
+/// The data cache is turned off, then cleaned and invalidated so that memory
+/// holds everything written so far, before the MMU is turned off.
+///
+/// Every data and unified cache up to the Level of Coherency is cleaned, see
+/// [`clean_and_invalidate_all`](crate::cache::clean_and_invalidate_all).
+///
+/// # Safety
+///
+/// The currently executing code, stack and data must be at the same
+/// addresses with the MMU off.
+#[cfg(any(doc, arm_architecture = "v7-a"))]
+pub unsafe fn disable() {
+    use crate::register::{BpIAll, Iciallu, TlbIAll};
+    Sctlr::modify(|s| {
+        s.set_c(false);
+    });
+    crate::cache::clean_and_invalidate_all();
+    Sctlr::modify(|s| {
+        s.set_m(false);
+        s.set_i(false);
+        s.set_z(false);
+    });
+    Iciallu::write();
+    TlbIAll::write();
+    BpIAll::write();
+    crate::asm::dsb();
+    crate::asm::isb();
+}
+
+/// Helpers shared by the tests in this module and its submodules
+#[cfg(test)]
+mod test_util {
+    extern crate std;
+
+    use std::boxed::Box;
+
+    /// Allocate a zeroed table on the heap, as the alignment is too large for the stack
+    pub(crate) fn zeroed<T>() -> Box<T> {
+        let layout = std::alloc::Layout::new::<T>();
+        assert!(layout.size() != 0);
+        // Safety: the layout is not zero-sized
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) } as *mut T;
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        // Safety: all-zeroes is a valid (all fault) translation table
+        unsafe { Box::from_raw(ptr) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::register::ifsr::FsrStatus;
 
     const SECTION_ATTRS_DEVICE_PERIPHERAL: SectionAttributes = SectionAttributes {
         non_global: false,
@@ -458,4 +1528,176 @@ mod tests {
     pub fn unaligned_section_address() {
         L1Section::new_with_addr_and_attrs(0x100001, SECTION_ATTRS_DEVICE_PERIPHERAL);
     }
+
+    #[test]
+    fn par_section() {
+        let par =
+            Par::new_with_raw_value(0x8010_0000 | (1 << 9) | (1 << 7) | (0b101 << 4) | (0b01 << 2));
+        let t = PhysTranslation::from_par(par, 0x0000_0ABC).unwrap();
+        assert_eq!(t.physical_address, 0x8010_0ABC);
+        assert_eq!(t.inner, ParInnerAttributes::WriteBackWriteAllocate);
+        assert_eq!(t.outer, ParOuterAttributes::WriteBackWriteAllocate);
+        assert!(t.shareable && t.outer_shareable && t.non_secure);
+        assert!(!t.supersection);
+    }
+
+    #[test]
+    fn par_supersection() {
+        let par = Par::new_with_raw_value(0x8012_0002);
+        let t = PhysTranslation::from_par(par, 0x0034_5678).unwrap();
+        assert_eq!(t.physical_address, 0x12_8034_5678);
+        assert!(t.supersection);
+    }
+
+    #[test]
+    fn par_fault() {
+        // Translation fault, second level: FS = 0b00111
+        let par = Par::new_with_raw_value((0b0111 << 1) | 1);
+        let fault = PhysTranslation::from_par(par, 0).unwrap_err();
+        assert_eq!(
+            fault.status(),
+            Ok(DfsrStatus::CommonFsr(
+                FsrStatus::TranslationFaultSecondLevel
+            ))
+        );
+        assert!(!fault.external);
+    }
+
+    const PAGE_ATTRS: PageAttributes = PageAttributes {
+        non_global: true,
+        shareable: true,
+        access: AccessPermissions::PrivilegedReadOnly,
+        memory_attrs: MemoryRegionAttributes::OuterAndInnerWriteBackWriteAlloc.as_raw(),
+        execute_never: true,
+    };
+
+    #[test]
+    fn small_page() {
+        let page = SmallPage::new_with_addr_and_attrs(0x1234_5000, PAGE_ATTRS);
+        assert_eq!(page.raw_value(), 0x1234_5E5F);
+        assert_eq!(page.phys_addr(), 0x1234_5000);
+        assert_eq!(page.page_attrs(), PAGE_ATTRS);
+        let entry = page.as_l2_entry();
+        assert_eq!(entry.entry_type(), L2EntryType::SmallPage);
+        assert_eq!(SmallPage::from_l2_entry(entry).unwrap(), page);
+        assert!(LargePage::from_l2_entry(entry).is_err());
+    }
+
+    #[test]
+    fn large_page() {
+        let page = LargePage::new_with_addr_and_attrs(0x1234_0000, PAGE_ATTRS);
+        assert_eq!(page.raw_value(), 0x1234_9E1D);
+        assert_eq!(page.phys_addr(), 0x1234_0000);
+        assert_eq!(page.page_attrs(), PAGE_ATTRS);
+        assert_eq!(page.as_l2_entry().entry_type(), L2EntryType::LargePage);
+    }
+
+    #[test]
+    fn page_table_pointer() {
+        let pt = L1PageTable::new_with_addr(0x8000_0400, u4::new(3), false, true);
+        assert_eq!(pt.raw_value(), 0x8000_0465);
+        assert_eq!(pt.l2_table_addr(), 0x8000_0400);
+        assert_eq!(L1PageTable::from_l1_entry(pt.as_l1_entry()).unwrap(), pt);
+        assert!(L1PageTable::from_l1_entry(L1_SECTION_MEMORY).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn unaligned_small_page_address() {
+        SmallPage::new_with_addr_and_attrs(0x1234_5800, PAGE_ATTRS);
+    }
+
+    const SUPERSECTION_ATTRS: SectionAttributes = SectionAttributes {
+        domain: u4::new(0),
+        ..SECTION_ATTRS_FULL_CACHEABLE
+    };
+
+    #[test]
+    fn supersection() {
+        let ss = Supersection::new_with_addr_and_attrs(0xA5_6000_0000, SUPERSECTION_ATTRS);
+        assert_eq!(ss.raw_value(), 0x6055_5D46);
+        assert_eq!(ss.phys_addr(), 0xA5_6000_0000);
+        assert_eq!(ss.section_attrs(), SUPERSECTION_ATTRS);
+        assert_eq!(Supersection::from_l1_entry(ss.as_l1_entry()).unwrap(), ss);
+        assert!(matches!(
+            Supersection::from_l1_entry(L1_SECTION_MEMORY),
+            Err(InvalidSupersection::NotSupersection)
+        ));
+        assert!(matches!(
+            Supersection::from_l1_entry(L1Section::ZERO),
+            Err(InvalidSupersection::EntryType(_))
+        ));
+
+        let mut entries = [L1Section::ZERO; NUM_L1_PAGE_TABLE_ENTRIES];
+        ss.write_to(&mut entries, 0x0100_0000);
+        assert_eq!(entries[0x00F], L1Section::ZERO);
+        assert!(entries[0x010..0x020].iter().all(|e| *e == ss.as_l1_entry()));
+        assert_eq!(entries[0x020], L1Section::ZERO);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unaligned_supersection_address() {
+        Supersection::new_with_addr_and_attrs(0x0080_0000, SUPERSECTION_ATTRS);
+    }
+
+    #[test]
+    fn tex_remap_linux() {
+        assert_eq!(TexRemap::LINUX.prrr().raw_value(), 0xFF0A_81A8);
+        assert_eq!(TexRemap::LINUX.nmrr().raw_value(), 0x40E0_40E0);
+    }
+
+    #[test]
+    fn remapped_section() {
+        for index in 0..8 {
+            let index = u3::new(index);
+            let attrs = SectionAttributes {
+                memory_attrs: MemoryRegionAttributes::Remapped { index }.as_raw(),
+                ..SECTION_ATTRS_FULL_CACHEABLE
+            };
+            let section = L1Section::new_with_addr_and_attrs(0x8000_0000, attrs);
+            let read_back = section.section_attrs().unwrap().memory_attrs;
+            assert_eq!(read_back.remap_index(), index);
+            assert_eq!(read_back.type_extensions.value() & 0b110, 0);
+        }
+    }
+
+    #[test]
+    fn split_table_sizes() {
+        fn check<T: Ttbr0Table>(size: usize) {
+            assert_eq!(core::mem::size_of::<T>(), size);
+            assert_eq!(core::mem::align_of::<T>(), size);
+            assert_eq!(T::NUM_ENTRIES * 4, size);
+        }
+        assert_eq!(L1Table::NUM_ENTRIES, NUM_L1_PAGE_TABLE_ENTRIES);
+        check::<L1TableN1>(8 * 1024);
+        check::<L1TableN4>(1024);
+        check::<L1TableN7>(128);
+    }
+
+    #[test]
+    fn mmu_config_default() {
+        let config = MmuConfig::DEFAULT;
+        assert_eq!(config.ttbr0.raw_value(), 0x0000_0048);
+        assert_eq!(config.dacr.raw_value(), 0x5555_5555);
+        let sctlr = config.sctlr(Sctlr::new_with_raw_value(0x00C5_0078));
+        assert_eq!(sctlr.raw_value(), 0x00C5_187D);
+
+        let config = MmuConfig {
+            data_cache: false,
+            ..MmuConfig::DEFAULT
+        };
+        let sctlr = config.sctlr(Sctlr::new_with_raw_value(0x00C5_187D));
+        assert_eq!(sctlr.raw_value(), 0x00C5_1879);
+    }
+
+    #[test]
+    fn mmu_config_ttbcr() {
+        let config = MmuConfig::DEFAULT;
+        assert_eq!(config.ttbcr(u3::new(0), false).raw_value(), 0x20);
+        assert_eq!(config.ttbcr(u3::new(L1TableN2::N), true).raw_value(), 0x02);
+        let ttbcr = config.ttbcr(u3::new(L1TableN2::N), false);
+        assert_eq!(ttbcr.ttbr1_boundary(), Some(0x4000_0000));
+        assert!(ttbcr.pd1());
+    }
 }
@@ -0,0 +1,221 @@
+//! Address spaces, each with their own translation table and ASID
+//!
+//! Non-global TLB entries are tagged with the ASID (*Address Space
+//! Identifier*) that was in CONTEXTIDR when they were loaded, so switching
+//! between [`AddressSpace`]s with different ASIDs doesn't require a TLB flush.
+//!
+//! There are only 255 ASIDs to go round (ASID 0 is reserved for use while
+//! switching), so the [`AsidAllocator`] hands them out lazily when an address
+//! space is activated. When it runs out it starts a new *generation*, flushes
+//! the whole TLB, and any address space holding an ASID from an older
+//! generation is given a fresh one the next time it is activated.
+//!
+//! This scheme assumes a single core, or one allocator (and one set of
+//! address spaces) per core.
+
+use super::{L1Table, Ttbr0Table};
+use crate::register::Ttbr0;
+
+/// The ASID that is never allocated, and is used while switching TTBR0
+const RESERVED_ASID: u8 = 0;
+
+/// An ASID, along with the generation it was allocated in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct AsidTag {
+    /// Zero if an ASID has never been allocated
+    generation: u32,
+    asid: u8,
+}
+
+impl AsidTag {
+    const UNALLOCATED: AsidTag = AsidTag {
+        generation: 0,
+        asid: RESERVED_ASID,
+    };
+}
+
+/// Hands out ASIDs to [`AddressSpace`]s
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AsidAllocator {
+    generation: u32,
+    next: u16,
+}
+
+impl AsidAllocator {
+    /// Create a new allocator, with every ASID available
+    pub const fn new() -> AsidAllocator {
+        AsidAllocator {
+            generation: 1,
+            next: RESERVED_ASID as u16 + 1,
+        }
+    }
+
+    /// The current generation
+    pub const fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Make sure `tag` holds an ASID from the current generation
+    ///
+    /// Returns `true` if a new generation was started, in which case the
+    /// whole TLB must be invalidated before the new ASID is used.
+    fn refresh(&mut self, tag: &mut AsidTag) -> bool {
+        if tag.generation == self.generation {
+            return false;
+        }
+        let mut rolled_over = false;
+        if self.next > u8::MAX as u16 {
+            self.generation = self.generation.wrapping_add(1);
+            if self.generation == AsidTag::UNALLOCATED.generation {
+                self.generation = 1;
+            }
+            self.next = RESERVED_ASID as u16 + 1;
+            rolled_over = true;
+        }
+        *tag = AsidTag {
+            generation: self.generation,
+            asid: self.next as u8,
+        };
+        self.next += 1;
+        rolled_over
+    }
+}
+
+impl Default for AsidAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A translation table for TTBR0, and the ASID its TLB entries are tagged with
+///
+/// The table may be smaller than a full [`L1Table`] if TTBCR.N is non-zero,
+/// with the rest of the address space (usually the kernel) mapped by TTBR1.
+pub struct AddressSpace<'a, T: Ttbr0Table = L1Table> {
+    table: &'a T,
+    ttbr0: Ttbr0,
+    tag: AsidTag,
+}
+
+impl<'a, T: Ttbr0Table> AddressSpace<'a, T> {
+    /// Create an address space using the given translation table
+    ///
+    /// The table walk attributes are taken from `ttbr0`; its address is
+    /// replaced with that of `table`. No ASID is allocated until the address
+    /// space is first activated.
+    pub fn new(table: &'a T, ttbr0: Ttbr0) -> AddressSpace<'a, T> {
+        AddressSpace {
+            table,
+            ttbr0: ttbr0.with_address(core::ptr::addr_of!(*table) as usize),
+            tag: AsidTag::UNALLOCATED,
+        }
+    }
+
+    /// The translation table for this address space
+    pub fn table(&self) -> &'a T {
+        self.table
+    }
+
+    /// The value written to TTBR0 when this address space is activated
+    pub fn ttbr0(&self) -> Ttbr0 {
+        self.ttbr0
+    }
+
+    /// The ASID currently held by this address space
+    ///
+    /// Returns `None` if it has never been activated, or its ASID belongs to
+    /// an old generation and may have been given to someone else.
+    pub fn asid(&self, allocator: &AsidAllocator) -> Option<u8> {
+        (self.tag.generation == allocator.generation).then_some(self.tag.asid)
+    }
+
+    /// Switch to this address space
+    ///
+    /// Sets TTBR0 and CONTEXTIDR, going through the reserved ASID so that no
+    /// TLB entries can be loaded with a mismatched ASID and table. Only
+    /// non-global TLB entries belonging to this address space are visible
+    /// afterwards.
+    ///
+    /// # Safety
+    ///
+    /// The translation table must remain valid for as long as this address
+    /// space is active, and together with TTBR1 must map the currently
+    /// executing code and stack. TTBCR.N must match the size of the table.
+    #[cfg(any(doc, arm_architecture = "v7-a"))]
+    pub unsafe fn activate(&mut self, allocator: &mut AsidAllocator) {
+        use crate::register::{BpIAll, Contextidr, TlbIAll};
+        crate::interrupt::free(|| {
+            let rolled_over = allocator.refresh(&mut self.tag);
+            unsafe {
+                Contextidr::write(Contextidr(RESERVED_ASID as u32));
+                crate::asm::isb();
+                Ttbr0::write(self.ttbr0);
+                crate::asm::isb();
+                if rolled_over {
+                    TlbIAll::write();
+                }
+                BpIAll::write();
+                crate::asm::dsb();
+                Contextidr::write(Contextidr(self.tag.asid as u32));
+            }
+            crate::asm::isb();
+        });
+    }
+
+    /// Invalidate the TLB entries belonging to this address space
+    ///
+    /// Does nothing if the address space doesn't hold a current ASID, as then
+    /// none of its entries can be in the TLB.
+    #[cfg(any(doc, arm_architecture = "v7-a"))]
+    pub fn invalidate_tlb(&self, allocator: &AsidAllocator) {
+        if let Some(asid) = self.asid(allocator) {
+            crate::asm::dsb();
+            crate::register::TlbIAsid::write(asid);
+            crate::register::BpIAll::write();
+            crate::asm::dsb();
+            crate::asm::isb();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_and_reuse() {
+        let mut allocator = AsidAllocator::new();
+        let mut a = AsidTag::UNALLOCATED;
+        let mut b = AsidTag::UNALLOCATED;
+        assert!(!allocator.refresh(&mut a));
+        assert!(!allocator.refresh(&mut b));
+        assert_eq!(a.asid, 1);
+        assert_eq!(b.asid, 2);
+        // Already current, so keeps its ASID
+        assert!(!allocator.refresh(&mut a));
+        assert_eq!(a.asid, 1);
+    }
+
+    #[test]
+    fn rollover() {
+        let mut allocator = AsidAllocator::new();
+        let mut first = AsidTag::UNALLOCATED;
+        assert!(!allocator.refresh(&mut first));
+        for _ in 2..=255 {
+            let mut tag = AsidTag::UNALLOCATED;
+            assert!(!allocator.refresh(&mut tag));
+        }
+        // Out of ASIDs: the next one starts a new generation
+        let mut next = AsidTag::UNALLOCATED;
+        assert!(allocator.refresh(&mut next));
+        assert_eq!(next.asid, 1);
+        assert_eq!(allocator.generation(), 2);
+        // ... and the first address space must get a new ASID
+        assert!(!allocator.refresh(&mut first));
+        assert_eq!(first.asid, 2);
+        assert_eq!(first.generation, 2);
+    }
+}
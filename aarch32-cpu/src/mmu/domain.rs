@@ -0,0 +1,105 @@
+//! Domain based access control for Short-descriptor translation tables
+//!
+//! Every section, supersection and L2 table is assigned to one of 16 domains.
+//! DACR sets the access mode of each domain, so changing it changes access to
+//! every mapping in a domain at once, without touching the translation tables
+//! or the TLB.
+
+use arbitrary_int::u4;
+
+use crate::register::Dacr;
+
+pub use crate::register::dacr::DomainAccess;
+
+/// Get the current access mode of a domain
+pub fn access(domain: u4) -> DomainAccess {
+    Dacr::read().d(domain.value() as usize)
+}
+
+/// Set the access mode of a domain
+pub fn set_access(domain: u4, access: DomainAccess) {
+    crate::interrupt::free(|| {
+        Dacr::modify(|dacr| dacr.set_d(domain.value() as usize, access));
+    });
+    sync();
+}
+
+/// Changes DACR, and puts it back when dropped
+///
+/// Guards must be dropped in the reverse order to which they were created, as
+/// each one restores the whole register to the value it had before.
+#[must_use = "DACR is restored when the guard is dropped"]
+pub struct DomainGuard {
+    previous: Dacr,
+}
+
+impl DomainGuard {
+    /// Replace the current DACR value with the result of `f`
+    pub fn new<F>(f: F) -> DomainGuard
+    where
+        F: FnOnce(Dacr) -> Dacr,
+    {
+        let previous = crate::interrupt::free(|| {
+            let previous = Dacr::read();
+            Dacr::write(f(previous));
+            previous
+        });
+        sync();
+        DomainGuard { previous }
+    }
+
+    /// Set the access mode of a single domain
+    pub fn set(domain: u4, access: DomainAccess) -> DomainGuard {
+        DomainGuard::new(|dacr| dacr.with_d(domain.value() as usize, access))
+    }
+
+    /// Set the access mode of every domain whose bit is set in `mask`
+    pub fn set_domains(mask: u16, access: DomainAccess) -> DomainGuard {
+        DomainGuard::new(|dacr| dacr.with_domains(mask, access))
+    }
+
+    /// The DACR value that will be restored when this guard is dropped
+    pub fn previous(&self) -> Dacr {
+        self.previous
+    }
+}
+
+impl Drop for DomainGuard {
+    fn drop(&mut self) {
+        Dacr::write(self.previous);
+        sync();
+    }
+}
+
+/// Make sure a DACR write is visible to the following instructions
+#[inline]
+fn sync() {
+    #[cfg(arm_architecture = "v7-a")]
+    crate::asm::isb();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_domains() {
+        assert_eq!(
+            Dacr::new_with_all(DomainAccess::Client).raw_value(),
+            0x5555_5555
+        );
+        assert_eq!(
+            Dacr::new_with_all(DomainAccess::Manager).raw_value(),
+            0xFFFF_FFFF
+        );
+    }
+
+    #[test]
+    fn domain_mask() {
+        let dacr = Dacr::new_with_all(DomainAccess::Client)
+            .with_domains(0b1000_0000_0000_0110, DomainAccess::NoAccess);
+        assert_eq!(dacr.raw_value(), 0x1555_5541);
+        assert_eq!(dacr.d(1), DomainAccess::NoAccess);
+        assert_eq!(dacr.d(3), DomainAccess::Client);
+    }
+}
@@ -0,0 +1,281 @@
+//! Evaluates accesses against Short-descriptor translation tables
+//!
+//! This models the stage 1 translation with SCTLR.AFE and SCTLR.TRE clear,
+//! so the AP bits are used as a three-bit permission field and the TEX, C
+//! and B bits are reported as they are found.
+
+use crate::evaluate::{Access, Evaluate, Fault, Outcome, Permissions};
+use crate::register::{dacr::DomainAccess, Dacr};
+
+use super::{
+    AccessPermissions, L1EntryType, L1PageTable, L1Section, L1Table, L2Entry, L2EntryType, L2Table,
+    LargePage, SectionAttributes, SmallPage, Supersection,
+};
+
+/// A set of translation tables, and the domain access control that goes with them
+pub struct Tables<'a> {
+    /// The L1 table to start from
+    pub l1: &'a L1Table,
+    /// The L2 tables that `l1` can point at, with the physical address of each
+    ///
+    /// An L1 entry pointing at an L2 table which is not in here gives
+    /// [`Fault::MissingL2Table`].
+    pub l2: &'a [(u32, &'a L2Table)],
+    /// Access control for each domain
+    pub dacr: Dacr,
+}
+
+impl Tables<'_> {
+    fn find_l2(&self, pt: L1PageTable) -> Result<&L2Table, Fault> {
+        let address = pt.l2_table_addr();
+        self.l2
+            .iter()
+            .find(|(phys, _)| *phys == address)
+            .map(|(_, table)| *table)
+            .ok_or(Fault::MissingL2Table { address })
+    }
+
+    /// Find the physical address and attributes of a virtual address
+    fn lookup(&self, address: u32) -> Result<(u64, SectionAttributes, u8), Fault> {
+        // Safety: we only read from the table
+        let entry: L1Section = unsafe { (*self.l1.entries.get())[address as usize >> 20] };
+        let raw = entry.raw_value();
+        match entry.entry_type() {
+            L1EntryType::Fault => Err(Fault::Translation { level: 1 }),
+            L1EntryType::PageTable => {
+                let pt = L1PageTable::new_with_raw_value(raw);
+                let table = self.find_l2(pt)?;
+                let idx = (address as usize >> 12) & 0xFF;
+                // Safety: we only read from the table
+                let entry: L2Entry = unsafe { (*table.entries.get())[idx] };
+                let (phys, offset_mask, page_attrs) = match entry.entry_type() {
+                    L2EntryType::Fault => return Err(Fault::Translation { level: 2 }),
+                    L2EntryType::SmallPage => {
+                        let page = SmallPage::new_with_raw_value(entry.raw_value());
+                        (page.phys_addr(), 0xFFF, page.page_attrs())
+                    }
+                    L2EntryType::LargePage => {
+                        let page = LargePage::new_with_raw_value(entry.raw_value());
+                        (page.phys_addr(), 0xFFFF, page.page_attrs())
+                    }
+                };
+                let attrs = SectionAttributes {
+                    non_global: page_attrs.non_global,
+                    p_bit: pt.p_bit(),
+                    shareable: page_attrs.shareable,
+                    access: page_attrs.access,
+                    memory_attrs: page_attrs.memory_attrs,
+                    domain: pt.domain(),
+                    execute_never: page_attrs.execute_never,
+                };
+                Ok(((phys | (address & offset_mask)) as u64, attrs, 2))
+            }
+            _ if raw & (1 << 18) != 0 => {
+                let ss = Supersection::new_with_raw_value(raw);
+                let phys = ss.phys_addr() | (address & 0x00FF_FFFF) as u64;
+                Ok((phys, ss.section_attrs(), 1))
+            }
+            _ => {
+                let attrs = entry
+                    .section_attrs()
+                    .map_err(|_| Fault::Translation { level: 1 })?;
+                let phys =
+                    ((entry.base_addr_upper_bits().value() as u32) << 20) | (address & 0x000F_FFFF);
+                Ok((phys as u64, attrs, 1))
+            }
+        }
+    }
+}
+
+/// What PL1 and PL0 may do with memory, given its AP bits
+///
+/// Returns (privileged read, privileged write, user read, user write). VMSAv7
+/// defines AP\[2:0\] = 0b111 as read-only, like 0b110; the reserved 0b100 is
+/// treated as no access.
+const fn access_permissions(access: AccessPermissions) -> (bool, bool, bool, bool) {
+    match access {
+        AccessPermissions::PermissionFault | AccessPermissions::_Reserved1 => {
+            (false, false, false, false)
+        }
+        AccessPermissions::PrivilegedOnly => (true, true, false, false),
+        AccessPermissions::NoUserWrite => (true, true, true, false),
+        AccessPermissions::FullAccess => (true, true, true, true),
+        AccessPermissions::PrivilegedReadOnly => (true, false, false, false),
+        AccessPermissions::ReadOnly | AccessPermissions::_Reserved2 => (true, false, true, false),
+    }
+}
+
+impl Evaluate for Tables<'_> {
+    type Attributes = SectionAttributes;
+
+    fn evaluate(&self, address: u32, access: Access) -> Result<Outcome<SectionAttributes>, Fault> {
+        let (physical_address, attributes, level) = self.lookup(address)?;
+        // Supersections report domain 0, which is the domain they are in
+        let domain = attributes.domain.value() as usize;
+        let permissions = match self.dacr.d(domain) {
+            DomainAccess::NoAccess | DomainAccess::Reserved => return Err(Fault::Domain { level }),
+            DomainAccess::Manager => Permissions::ALL,
+            DomainAccess::Client => {
+                let (pr, pw, ur, uw) = access_permissions(attributes.access);
+                if access.privileged {
+                    Permissions::new(pr, pw, attributes.execute_never)
+                } else {
+                    Permissions::new(ur, uw, attributes.execute_never)
+                }
+            }
+        };
+        if !permissions.allows(access.kind) {
+            return Err(Fault::Permission { level });
+        }
+        Ok(Outcome {
+            physical_address,
+            attributes,
+            permissions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::evaluate::AccessType;
+    use crate::mmu::test_util::zeroed;
+    use crate::mmu::{mapper::Mapper, MemoryRegionAttributes};
+    use arbitrary_int::u4;
+    use std::boxed::Box;
+
+    const ATTRS: SectionAttributes = SectionAttributes {
+        non_global: false,
+        p_bit: false,
+        shareable: true,
+        access: AccessPermissions::PrivilegedOnly,
+        memory_attrs: MemoryRegionAttributes::OuterAndInnerWriteBackWriteAlloc.as_raw(),
+        domain: u4::new(1),
+        execute_never: true,
+    };
+
+    #[test]
+    fn sections_and_pages() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 1]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        mapper
+            .map(0x1000_0000, 0x8000_0000, 0x0010_1000, ATTRS)
+            .unwrap();
+        let l2 = [(core::ptr::addr_of!(pool[0]) as usize as u32, &pool[0])];
+        let tables = Tables {
+            l1: &l1,
+            l2: &l2,
+            dacr: Dacr::new_with_all(DomainAccess::Client),
+        };
+
+        let outcome = tables
+            .evaluate(0x1000_1234, Access::PRIVILEGED_WRITE)
+            .unwrap();
+        assert_eq!(outcome.physical_address, 0x8000_1234);
+        assert_eq!(outcome.attributes, ATTRS);
+        assert_eq!(outcome.permissions, Permissions::new(true, true, true));
+        assert_eq!(
+            tables
+                .evaluate(0x1010_0010, Access::PRIVILEGED_READ)
+                .map(|o| o.physical_address),
+            Ok(0x8010_0010)
+        );
+        assert_eq!(
+            tables.evaluate(0x1000_0000, Access::USER_READ),
+            Err(Fault::Permission { level: 1 })
+        );
+        assert_eq!(
+            tables.evaluate(0x1010_0000, Access::new(AccessType::Execute, true)),
+            Err(Fault::Permission { level: 2 })
+        );
+        assert_eq!(
+            tables.evaluate(0x1010_1000, Access::PRIVILEGED_READ),
+            Err(Fault::Translation { level: 2 })
+        );
+        assert_eq!(
+            tables.evaluate(0x2000_0000, Access::PRIVILEGED_READ),
+            Err(Fault::Translation { level: 1 })
+        );
+    }
+
+    #[test]
+    fn domains() {
+        let l1: Box<L1Table> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &[]) };
+        mapper
+            .map(0x1000_0000, 0x1000_0000, 0x0010_0000, ATTRS)
+            .unwrap();
+        let mut tables = Tables {
+            l1: &l1,
+            l2: &[],
+            dacr: Dacr::new_with_all(DomainAccess::Client)
+                .with_domains(1 << 1, DomainAccess::NoAccess),
+        };
+        assert_eq!(
+            tables.evaluate(0x1000_0000, Access::PRIVILEGED_READ),
+            Err(Fault::Domain { level: 1 })
+        );
+        tables.dacr = tables.dacr.with_domains(1 << 1, DomainAccess::Manager);
+        assert_eq!(
+            tables
+                .evaluate(0x1000_0000, Access::USER_EXECUTE)
+                .map(|o| o.permissions),
+            Ok(Permissions::ALL)
+        );
+    }
+
+    #[test]
+    fn reserved_access_permissions() {
+        assert_eq!(
+            access_permissions(AccessPermissions::_Reserved2),
+            access_permissions(AccessPermissions::ReadOnly)
+        );
+        assert_eq!(
+            access_permissions(AccessPermissions::_Reserved1),
+            (false, false, false, false)
+        );
+    }
+
+    const PAGE_ATTRS: crate::mmu::PageAttributes = crate::mmu::PageAttributes {
+        non_global: false,
+        shareable: false,
+        access: AccessPermissions::FullAccess,
+        memory_attrs: MemoryRegionAttributes::StronglyOrdered.as_raw(),
+        execute_never: true,
+    };
+
+    #[test]
+    fn l2_tables_by_physical_address() {
+        let l1: Box<L1Table> = zeroed();
+        let l2: Box<L2Table> = zeroed();
+        // Tables built for the target point at the table's physical address
+        let pt = L1PageTable::new_with_addr(0x8000_0400, u4::new(0), false, false);
+        unsafe {
+            (*l1.entries.get())[0x100] = pt.as_l1_entry();
+            (*l2.entries.get())[1] =
+                SmallPage::new_with_addr_and_attrs(0x4000_0000, PAGE_ATTRS).as_l2_entry();
+        }
+        let mut tables = Tables {
+            l1: &l1,
+            l2: &[],
+            dacr: Dacr::new_with_all(DomainAccess::Client),
+        };
+        assert_eq!(
+            tables.evaluate(0x1000_1000, Access::PRIVILEGED_READ),
+            Err(Fault::MissingL2Table {
+                address: 0x8000_0400
+            })
+        );
+        let supplied = [(0x8000_0400, &*l2)];
+        tables.l2 = &supplied;
+        assert_eq!(
+            tables
+                .evaluate(0x1000_1010, Access::PRIVILEGED_READ)
+                .map(|o| o.physical_address),
+            Ok(0x4000_0010)
+        );
+    }
+}
@@ -0,0 +1,626 @@
+//! Code and types for the Long-descriptor translation table format (LPAE)
+//!
+//! The Large Physical Address Extension adds a three-level translation table
+//! format with 64-bit descriptors, 40-bit physical addresses and memory
+//! attributes taken from MAIR0/MAIR1 rather than TEX/C/B bits.
+//!
+//! With TTBCR.T0SZ = 0, TTBR0 covers the whole 4 GiB address space and points
+//! at an [`L1Table`] of four entries, each of which covers 1 GiB. Each of
+//! those can be a 1 GiB block, or point at an [`L2Table`] whose 512 entries
+//! each cover 2 MiB. Those in turn can be a 2 MiB block, or point at an
+//! [`L3Table`] whose 512 entries each map a 4 KiB page.
+
+use arbitrary_int::{u2, u28, u3};
+
+use crate::register::{Mair0, Mair1};
+
+pub use crate::register::ttbcr::Shareability;
+
+/// Number of entries in a first level table, when TTBCR.T0SZ is 0
+pub const NUM_L1_ENTRIES: usize = 4;
+
+/// Number of entries in a second or third level table
+pub const NUM_TABLE_ENTRIES: usize = 512;
+
+/// Size of the region mapped by a first level block
+pub const L1_BLOCK_SIZE: u64 = 1 << 30;
+
+/// Size of the region mapped by a second level block
+pub const L2_BLOCK_SIZE: u64 = 1 << 21;
+
+/// Size of the region mapped by a third level page
+pub const PAGE_SIZE: u64 = 1 << 12;
+
+/// Holds a first level Long-descriptor table with appropriate alignment
+#[repr(C, align(32))]
+pub struct L1Table {
+    pub entries: core::cell::UnsafeCell<[Descriptor; NUM_L1_ENTRIES]>,
+}
+
+/// This type is thread-safe
+unsafe impl Sync for L1Table {}
+
+/// Holds a second level Long-descriptor table with appropriate alignment
+#[repr(C, align(4096))]
+pub struct L2Table {
+    pub entries: core::cell::UnsafeCell<[Descriptor; NUM_TABLE_ENTRIES]>,
+}
+
+/// This type is thread-safe
+unsafe impl Sync for L2Table {}
+
+/// Holds a third level Long-descriptor table with appropriate alignment
+#[repr(C, align(4096))]
+pub struct L3Table {
+    pub entries: core::cell::UnsafeCell<[Descriptor; NUM_TABLE_ENTRIES]>,
+}
+
+/// This type is thread-safe
+unsafe impl Sync for L3Table {}
+
+/// Represents a descriptor of the wrong type
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("invalid descriptor {0:#018x}")]
+pub struct InvalidDescriptor(pub u64);
+
+/// Access permissions (the `AP[2:1]` bits) for a block or page
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum AccessPermissions {
+    /// Read/write at PL1, no access at PL0
+    PrivilegedReadWrite = 0b00,
+    /// Read/write at any privilege level
+    ReadWrite = 0b01,
+    /// Read-only at PL1, no access at PL0
+    PrivilegedReadOnly = 0b10,
+    /// Read-only at any privilege level
+    ReadOnly = 0b11,
+}
+
+/// A single memory attribute encoding, as stored in MAIR0/MAIR1
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MairAttr(pub u8);
+
+impl MairAttr {
+    /// Strongly-ordered memory
+    pub const STRONGLY_ORDERED: MairAttr = MairAttr(0x00);
+    /// Device memory
+    pub const DEVICE: MairAttr = MairAttr(0x04);
+    /// Normal memory, Inner and Outer Non-cacheable
+    pub const NORMAL_NON_CACHEABLE: MairAttr = MairAttr(0x44);
+    /// Normal memory, Inner and Outer Write-Through, Read-Allocate
+    pub const NORMAL_WRITE_THROUGH: MairAttr = MairAttr(0xAA);
+    /// Normal memory, Inner and Outer Write-Back, Read-Allocate and Write-Allocate
+    pub const NORMAL_WRITE_BACK: MairAttr = MairAttr(0xFF);
+}
+
+/// The contents of MAIR0 and MAIR1, as eight indexed memory attributes
+///
+/// Descriptors refer to these attributes by index, through
+/// [`Attributes::attr_index`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MairConfig {
+    pub attrs: [MairAttr; 8],
+}
+
+impl MairConfig {
+    /// The value to write to MAIR0 (attributes 0 to 3)
+    pub const fn mair0(&self) -> Mair0 {
+        Mair0(u32::from_le_bytes([
+            self.attrs[0].0,
+            self.attrs[1].0,
+            self.attrs[2].0,
+            self.attrs[3].0,
+        ]))
+    }
+
+    /// The value to write to MAIR1 (attributes 4 to 7)
+    pub const fn mair1(&self) -> Mair1 {
+        Mair1(u32::from_le_bytes([
+            self.attrs[4].0,
+            self.attrs[5].0,
+            self.attrs[6].0,
+            self.attrs[7].0,
+        ]))
+    }
+
+    /// Write these attributes to MAIR0 and MAIR1
+    ///
+    /// # Safety
+    ///
+    /// Changing the attributes changes the meaning of every descriptor in the
+    /// active translation tables.
+    pub unsafe fn write(&self) {
+        unsafe {
+            Mair0::write(self.mair0());
+            Mair1::write(self.mair1());
+        }
+    }
+}
+
+/// Attributes for a block or page descriptor
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attributes {
+    /// Index of the memory attributes in MAIR0/MAIR1
+    pub attr_index: u3,
+    /// NS bit
+    pub non_secure: bool,
+    /// `AP[2:1]` bits
+    pub access: AccessPermissions,
+    /// SH bits
+    pub shareability: Shareability,
+    /// AF bit. Accessing an entry with this clear generates an Access flag fault.
+    pub access_flag: bool,
+    /// nG bit
+    pub non_global: bool,
+    /// Contiguous hint bit
+    pub contiguous: bool,
+    /// PXN bit
+    pub privileged_execute_never: bool,
+    /// XN bit
+    pub execute_never: bool,
+}
+
+/// A raw entry in a Long-descriptor table
+///
+/// Use [`BlockDescriptor`], [`TableDescriptor`] or [`PageDescriptor`] to
+/// construct or decode these.
+#[bitbybit::bitfield(u64, default = 0, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Descriptor {
+    #[bits(0..=1, r)]
+    type_bits: u2,
+}
+
+impl Descriptor {
+    /// An invalid descriptor, which generates a Translation fault
+    pub const INVALID: Descriptor = Descriptor::ZERO;
+
+    /// Is this a valid (block, table or page) descriptor?
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.raw_value() & 0b1 != 0
+    }
+
+    /// Is this a table descriptor (at the first or second level) or a page
+    /// descriptor (at the third level)?
+    #[inline]
+    pub const fn is_table_or_page(&self) -> bool {
+        self.type_bits().value() == 0b11
+    }
+
+    /// Is this a block descriptor (at the first or second level)?
+    #[inline]
+    pub const fn is_block(&self) -> bool {
+        self.type_bits().value() == 0b01
+    }
+}
+
+/// Block descriptor, mapping a 1 GiB (first level) or 2 MiB (second level)
+/// region to a physical address.
+///
+/// The ARMv7-A and ArmV7-R architecture reference manual p.1366 specifies these attributes in
+/// more detail.
+#[bitbybit::bitfield(u64, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlockDescriptor {
+    #[bit(54, rw)]
+    xn: bool,
+    #[bit(53, rw)]
+    pxn: bool,
+    #[bit(52, rw)]
+    contiguous: bool,
+    /// Output address bits `[39:12]`.
+    #[bits(12..=39, rw)]
+    output_addr: u28,
+    #[bit(11, rw)]
+    ng: bool,
+    #[bit(10, rw)]
+    af: bool,
+    #[bits(8..=9, rw)]
+    sh: Shareability,
+    #[bits(6..=7, rw)]
+    ap: AccessPermissions,
+    #[bit(5, rw)]
+    ns: bool,
+    #[bits(2..=4, rw)]
+    attr_index: u3,
+    #[bits(0..=1, rw)]
+    type_bits: u2,
+}
+
+impl BlockDescriptor {
+    /// Generates a new first level (1 GiB) block.
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 1 GiB, or wider than 40 bits.
+    pub const fn new_l1(phys_addr: u64, attrs: Attributes) -> Self {
+        if phys_addr & (L1_BLOCK_SIZE - 1) != 0 {
+            panic!("physical base address for L1 block must be aligned to 1 GiB");
+        }
+        Self::new_unchecked(phys_addr, attrs)
+    }
+
+    /// Generates a new second level (2 MiB) block.
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 2 MiB, or wider than 40 bits.
+    pub const fn new_l2(phys_addr: u64, attrs: Attributes) -> Self {
+        if phys_addr & (L2_BLOCK_SIZE - 1) != 0 {
+            panic!("physical base address for L2 block must be aligned to 2 MiB");
+        }
+        Self::new_unchecked(phys_addr, attrs)
+    }
+
+    const fn new_unchecked(phys_addr: u64, attrs: Attributes) -> Self {
+        if phys_addr >> 40 != 0 {
+            panic!("physical base address must fit in 40 bits");
+        }
+        BlockDescriptor::builder()
+            .with_xn(attrs.execute_never)
+            .with_pxn(attrs.privileged_execute_never)
+            .with_contiguous(attrs.contiguous)
+            .with_output_addr(u28::new((phys_addr >> 12) as u32))
+            .with_ng(attrs.non_global)
+            .with_af(attrs.access_flag)
+            .with_sh(attrs.shareability)
+            .with_ap(attrs.access)
+            .with_ns(attrs.non_secure)
+            .with_attr_index(attrs.attr_index)
+            .with_type_bits(u2::new(0b01))
+            .build()
+    }
+
+    /// The physical address this block maps to.
+    #[inline]
+    pub const fn phys_addr(&self) -> u64 {
+        (self.output_addr().value() as u64) << 12
+    }
+
+    /// Retrieve the block attributes.
+    #[inline]
+    pub const fn attrs(&self) -> Attributes {
+        Attributes {
+            attr_index: self.attr_index(),
+            non_secure: self.ns(),
+            access: self.ap(),
+            shareability: self.sh(),
+            access_flag: self.af(),
+            non_global: self.ng(),
+            contiguous: self.contiguous(),
+            privileged_execute_never: self.pxn(),
+            execute_never: self.xn(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in a table.
+    #[inline]
+    pub const fn as_descriptor(&self) -> Descriptor {
+        Descriptor::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret a first or second level table entry as a block.
+    #[inline]
+    pub const fn from_descriptor(desc: Descriptor) -> Result<Self, InvalidDescriptor> {
+        if !desc.is_block() {
+            return Err(InvalidDescriptor(desc.raw_value()));
+        }
+        Ok(Self::new_with_raw_value(desc.raw_value()))
+    }
+}
+
+/// Page descriptor, mapping a 4 KiB region to a physical address.
+///
+/// Only valid in a third level table. The ARMv7-A and ArmV7-R architecture
+/// reference manual p.1366 specifies these attributes in more detail.
+#[bitbybit::bitfield(u64, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct PageDescriptor {
+    #[bit(54, rw)]
+    xn: bool,
+    #[bit(53, rw)]
+    pxn: bool,
+    #[bit(52, rw)]
+    contiguous: bool,
+    /// Output address bits `[39:12]`.
+    #[bits(12..=39, rw)]
+    output_addr: u28,
+    #[bit(11, rw)]
+    ng: bool,
+    #[bit(10, rw)]
+    af: bool,
+    #[bits(8..=9, rw)]
+    sh: Shareability,
+    #[bits(6..=7, rw)]
+    ap: AccessPermissions,
+    #[bit(5, rw)]
+    ns: bool,
+    #[bits(2..=4, rw)]
+    attr_index: u3,
+    #[bits(0..=1, rw)]
+    type_bits: u2,
+}
+
+impl PageDescriptor {
+    /// Generates a new third level (4 KiB) page.
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 4 KiB, or wider than 40 bits.
+    pub const fn new_with_addr_and_attrs(phys_addr: u64, attrs: Attributes) -> Self {
+        if phys_addr & (PAGE_SIZE - 1) != 0 {
+            panic!("physical base address for page must be aligned to 4 KiB");
+        }
+        if phys_addr >> 40 != 0 {
+            panic!("physical base address must fit in 40 bits");
+        }
+        PageDescriptor::builder()
+            .with_xn(attrs.execute_never)
+            .with_pxn(attrs.privileged_execute_never)
+            .with_contiguous(attrs.contiguous)
+            .with_output_addr(u28::new((phys_addr >> 12) as u32))
+            .with_ng(attrs.non_global)
+            .with_af(attrs.access_flag)
+            .with_sh(attrs.shareability)
+            .with_ap(attrs.access)
+            .with_ns(attrs.non_secure)
+            .with_attr_index(attrs.attr_index)
+            .with_type_bits(u2::new(0b11))
+            .build()
+    }
+
+    /// The physical address this page maps to.
+    #[inline]
+    pub const fn phys_addr(&self) -> u64 {
+        (self.output_addr().value() as u64) << 12
+    }
+
+    /// Retrieve the page attributes.
+    #[inline]
+    pub const fn attrs(&self) -> Attributes {
+        Attributes {
+            attr_index: self.attr_index(),
+            non_secure: self.ns(),
+            access: self.ap(),
+            shareability: self.sh(),
+            access_flag: self.af(),
+            non_global: self.ng(),
+            contiguous: self.contiguous(),
+            privileged_execute_never: self.pxn(),
+            execute_never: self.xn(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in an [`L3Table`].
+    #[inline]
+    pub const fn as_descriptor(&self) -> Descriptor {
+        Descriptor::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret a third level table entry as a page.
+    #[inline]
+    pub const fn from_descriptor(desc: Descriptor) -> Result<Self, InvalidDescriptor> {
+        if !desc.is_table_or_page() {
+            return Err(InvalidDescriptor(desc.raw_value()));
+        }
+        Ok(Self::new_with_raw_value(desc.raw_value()))
+    }
+}
+
+/// Attributes which a table descriptor applies to everything in the next level table
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableAttributes {
+    /// NSTable bit
+    pub non_secure: bool,
+    /// APTable bits (0 means no effect on subsequent levels)
+    pub access_limit: u2,
+    /// XNTable bit
+    pub execute_never: bool,
+    /// PXNTable bit
+    pub privileged_execute_never: bool,
+}
+
+/// Table descriptor, pointing to a second or third level table.
+///
+/// Only valid in a first or second level table. The ARMv7-A and ArmV7-R
+/// architecture reference manual p.1365 specifies these attributes in more detail.
+#[bitbybit::bitfield(u64, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub struct TableDescriptor {
+    #[bit(63, rw)]
+    ns_table: bool,
+    #[bits(61..=62, rw)]
+    ap_table: u2,
+    #[bit(60, rw)]
+    xn_table: bool,
+    #[bit(59, rw)]
+    pxn_table: bool,
+    /// Next-level table address bits `[39:12]`.
+    #[bits(12..=39, rw)]
+    table_addr: u28,
+    #[bits(0..=1, rw)]
+    type_bits: u2,
+}
+
+impl TableDescriptor {
+    /// Generates a new descriptor pointing at the table with the given physical address.
+    ///
+    /// # Panics
+    ///
+    /// Table address not aligned to 4 KiB, or wider than 40 bits.
+    pub const fn new_with_addr_and_attrs(table_addr: u64, attrs: TableAttributes) -> Self {
+        if table_addr & (PAGE_SIZE - 1) != 0 {
+            panic!("next level table address must be aligned to 4 KiB");
+        }
+        if table_addr >> 40 != 0 {
+            panic!("next level table address must fit in 40 bits");
+        }
+        TableDescriptor::builder()
+            .with_ns_table(attrs.non_secure)
+            .with_ap_table(attrs.access_limit)
+            .with_xn_table(attrs.execute_never)
+            .with_pxn_table(attrs.privileged_execute_never)
+            .with_table_addr(u28::new((table_addr >> 12) as u32))
+            .with_type_bits(u2::new(0b11))
+            .build()
+    }
+
+    /// The physical address of the next level table.
+    #[inline]
+    pub const fn next_table_addr(&self) -> u64 {
+        (self.table_addr().value() as u64) << 12
+    }
+
+    /// Retrieve the table attributes.
+    #[inline]
+    pub const fn attrs(&self) -> TableAttributes {
+        TableAttributes {
+            non_secure: self.ns_table(),
+            access_limit: self.ap_table(),
+            execute_never: self.xn_table(),
+            privileged_execute_never: self.pxn_table(),
+        }
+    }
+
+    /// Convert to an entry which can be stored in a table.
+    #[inline]
+    pub const fn as_descriptor(&self) -> Descriptor {
+        Descriptor::new_with_raw_value(self.raw_value())
+    }
+
+    /// Interpret a first or second level table entry as a table pointer.
+    #[inline]
+    pub const fn from_descriptor(desc: Descriptor) -> Result<Self, InvalidDescriptor> {
+        if !desc.is_table_or_page() {
+            return Err(InvalidDescriptor(desc.raw_value()));
+        }
+        Ok(Self::new_with_raw_value(desc.raw_value()))
+    }
+}
+
+/// Select the Long-descriptor format, with TTBR0 covering the whole 4 GiB address space
+///
+/// Table walks are Inner Shareable and Write-Back Write-Allocate cacheable.
+/// TTBR1 walks are disabled.
+pub const fn ttbcr() -> crate::register::Ttbcr {
+    use crate::register::ttbr0::Region;
+    crate::register::Ttbcr::DEFAULT
+        .with_eae(true)
+        .with_epd1(true)
+        .with_sh0(Shareability::InnerShareable)
+        .with_orgn0(Region::WriteBackWriteAllocateCacheable)
+        .with_irgn0(Region::WriteBackWriteAllocateCacheable)
+        .with_t0sz(u3::new(0))
+}
+
+/// Point TTBR0 at a Long-descriptor first level table, and switch to the Long-descriptor format
+///
+/// This writes MAIR0/MAIR1, TTBCR and the 64-bit TTBR0, but does not turn the
+/// MMU on. The table is assumed to be identity mapped, so that its virtual
+/// address is also its physical address.
+///
+/// # Safety
+///
+/// The MMU must be disabled, or the new tables must map the currently
+/// executing code and data at the same addresses. Any stale TLB entries must
+/// be invalidated before use.
+#[cfg(any(doc, arm_architecture = "v7-a"))]
+pub unsafe fn configure(table: &'static L1Table, mair: &MairConfig) {
+    use crate::register::{Ttbcr, Ttbr0Lpae};
+    unsafe {
+        mair.write();
+        Ttbcr::write(ttbcr());
+        Ttbr0Lpae::write(Ttbr0Lpae::DEFAULT.with_address(core::ptr::addr_of!(*table) as u64));
+    }
+    crate::asm::isb();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NORMAL_ATTRS: Attributes = Attributes {
+        attr_index: u3::new(1),
+        non_secure: false,
+        access: AccessPermissions::ReadWrite,
+        shareability: Shareability::InnerShareable,
+        access_flag: true,
+        non_global: false,
+        contiguous: false,
+        privileged_execute_never: false,
+        execute_never: true,
+    };
+
+    #[test]
+    fn block_descriptor() {
+        let block = BlockDescriptor::new_l2(0x12_3440_0000, NORMAL_ATTRS);
+        assert_eq!(block.raw_value(), 0x0040_0012_3440_0745);
+        assert_eq!(block.phys_addr(), 0x12_3440_0000);
+        assert_eq!(block.attrs(), NORMAL_ATTRS);
+        let desc = block.as_descriptor();
+        assert!(desc.is_valid() && desc.is_block());
+        assert_eq!(BlockDescriptor::from_descriptor(desc).unwrap(), block);
+        assert!(TableDescriptor::from_descriptor(desc).is_err());
+    }
+
+    #[test]
+    fn page_and_table_descriptors() {
+        let page = PageDescriptor::new_with_addr_and_attrs(0x8000_1000, NORMAL_ATTRS);
+        assert_eq!(page.raw_value(), 0x0040_0000_8000_1747);
+        assert_eq!(page.attrs(), NORMAL_ATTRS);
+
+        let table = TableDescriptor::new_with_addr_and_attrs(
+            0x8020_0000,
+            TableAttributes {
+                execute_never: true,
+                ..TableAttributes::default()
+            },
+        );
+        assert_eq!(table.raw_value(), 0x1000_0000_8020_0003);
+        assert_eq!(table.next_table_addr(), 0x8020_0000);
+        assert!(!Descriptor::INVALID.is_valid());
+    }
+
+    #[test]
+    fn mair_config() {
+        let mair = MairConfig {
+            attrs: [
+                MairAttr::DEVICE,
+                MairAttr::NORMAL_WRITE_BACK,
+                MairAttr::NORMAL_NON_CACHEABLE,
+                MairAttr::STRONGLY_ORDERED,
+                MairAttr::NORMAL_WRITE_THROUGH,
+                MairAttr::STRONGLY_ORDERED,
+                MairAttr::STRONGLY_ORDERED,
+                MairAttr::STRONGLY_ORDERED,
+            ],
+        };
+        assert_eq!(mair.mair0().0, 0x0044_FF04);
+        assert_eq!(mair.mair1().0, 0x0000_00AA);
+        assert_eq!(ttbcr().raw_value(), 0x8080_3500);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unaligned_l1_block() {
+        BlockDescriptor::new_l1(0x4020_0000, NORMAL_ATTRS);
+    }
+}
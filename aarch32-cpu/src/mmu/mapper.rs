@@ -0,0 +1,835 @@
+//! A runtime mapper for Short-descriptor translation tables
+//!
+//! The [`Mapper`] edits an [`L1Table`] in place, using supersections and
+//! sections where the alignment allows, and falling back to large and small
+//! pages in L2 tables taken from a caller-provided pool. Sections and large
+//! pages are split when only part of them is unmapped or re-protected.
+//!
+//! An L2 table from the pool is in use if, and only if, an L1 entry points at
+//! it. When every page in an L2 table has been unmapped, the L1 entry is
+//! cleared and the table goes back to the pool.
+//!
+//! Splitting a live entry uses break-before-make: the old entries are
+//! cleared and the TLBs invalidated before the smaller entries are written.
+//! The memory being split is briefly unmapped, so it must not hold the code,
+//! stack or tables the mapper is using. When the Multiprocessing Extensions
+//! are present, TLB and branch predictor maintenance is broadcast to the
+//! whole Inner Shareable domain.
+
+use super::{
+    L1EntryType, L1PageTable, L1Section, L1Table, L2Entry, L2EntryType, L2Table, LargePage,
+    PageAttributes, SectionAttributes, SmallPage, Supersection, NUM_L2_PAGE_TABLE_ENTRIES,
+};
+
+const SMALL_PAGE_SIZE: u64 = 4 * 1024;
+const LARGE_PAGE_SIZE: u64 = 64 * 1024;
+const SECTION_SIZE: u64 = 1024 * 1024;
+const SUPERSECTION_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Errors from the [`Mapper`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// An address or length was not a multiple of 4 KiB
+    Unaligned,
+    /// The range was empty, or ran off the end of the address space
+    InvalidRange,
+    /// Part of the range to be mapped was already mapped
+    AlreadyMapped,
+    /// Part of the range to be protected was not mapped
+    NotMapped,
+    /// The L2 table pool has no free tables left
+    OutOfTables,
+    /// The attributes asked for a different domain to the L2 table which
+    /// already covers part of this megabyte
+    DomainMismatch,
+    /// An L1 entry points at an L2 table which did not come from the pool
+    UnknownTable,
+    /// A supersection which would have to be split maps a physical address
+    /// above 4 GiB, which sections cannot express
+    PhysAddrTooWide,
+}
+
+/// What an L1 entry currently holds
+enum L1Kind {
+    Fault,
+    Section(L1Section),
+    Supersection(Supersection),
+    PageTable(L1PageTable),
+}
+
+/// Edits translation tables at runtime
+pub struct Mapper<'a> {
+    l1: &'a L1Table,
+    pool: &'a [L2Table],
+}
+
+impl<'a> Mapper<'a> {
+    /// Create a new mapper for the given L1 table and pool of L2 tables
+    ///
+    /// Any L2 tables referenced by `l1` must be in `pool`, and any table in
+    /// `pool` which is not referenced by `l1` is considered free.
+    ///
+    /// # Safety
+    ///
+    /// * Nothing else may modify `l1` or the tables in `pool` while this
+    ///   mapper exists.
+    /// * The tables must be identity mapped, because their virtual addresses
+    ///   are written into the L1 table as physical addresses.
+    pub unsafe fn new(l1: &'a L1Table, pool: &'a [L2Table]) -> Mapper<'a> {
+        Mapper { l1, pool }
+    }
+
+    /// Map `len` bytes at `virt` to the physical memory at `phys`
+    ///
+    /// All of the range must currently be unmapped. If we run out of L2
+    /// tables part way through, anything mapped so far is unmapped again.
+    pub fn map(
+        &mut self,
+        virt: u32,
+        phys: u32,
+        len: u32,
+        attrs: SectionAttributes,
+    ) -> Result<(), Error> {
+        check_range(virt, len)?;
+        check_range(phys, len)?;
+        if !self.is_unmapped(virt, len)? {
+            return Err(Error::AlreadyMapped);
+        }
+        let mut done = 0;
+        let mut result = Ok(());
+        while done < len as u64 {
+            match self.map_one(
+                virt as u64 + done,
+                phys as u64 + done,
+                len as u64 - done,
+                attrs,
+            ) {
+                Ok(step) => done += step,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if result.is_err() && done > 0 {
+            // Unmapping whole entries we just created never needs a new table
+            let _ = self.update(virt, done as u32, None);
+        }
+        self.sync();
+        result
+    }
+
+    /// Unmap `len` bytes at `virt`
+    ///
+    /// Parts of the range which are not mapped are ignored. Sections and large
+    /// pages which are only partly covered are split, which may need an L2
+    /// table from the pool. The whole range is checked first, so on error
+    /// nothing has been changed.
+    pub fn unmap(&mut self, virt: u32, len: u32) -> Result<(), Error> {
+        check_range(virt, len)?;
+        self.check_update(virt, len, None)?;
+        let result = self.update(virt, len, None);
+        self.sync();
+        result
+    }
+
+    /// Change the attributes of `len` bytes at `virt`, keeping the physical addresses
+    ///
+    /// All of the range must currently be mapped. Sections and large pages
+    /// which are only partly covered are split, which may need an L2 table
+    /// from the pool. The whole range is checked first, so on error nothing
+    /// has been changed.
+    pub fn protect(&mut self, virt: u32, len: u32, attrs: SectionAttributes) -> Result<(), Error> {
+        check_range(virt, len)?;
+        if !self.is_fully_mapped(virt, len)? {
+            return Err(Error::NotMapped);
+        }
+        self.check_update(virt, len, Some(&attrs))?;
+        let result = self.update(virt, len, Some(attrs));
+        self.sync();
+        result
+    }
+
+    /// Map a single entry at the start of the given range, returning how many bytes it covered
+    fn map_one(
+        &mut self,
+        virt: u64,
+        phys: u64,
+        remaining: u64,
+        attrs: SectionAttributes,
+    ) -> Result<u64, Error> {
+        let idx = (virt / SECTION_SIZE) as usize;
+        if let L1Kind::Fault = self.l1_kind(idx) {
+            if virt % SUPERSECTION_SIZE == 0
+                && phys % SUPERSECTION_SIZE == 0
+                && remaining >= SUPERSECTION_SIZE
+                && attrs.domain.value() == 0
+                && (idx..idx + Supersection::NUM_ENTRIES)
+                    .all(|i| matches!(self.l1_kind(i), L1Kind::Fault))
+            {
+                let ss = Supersection::new_with_addr_and_attrs(phys, attrs);
+                for i in idx..idx + Supersection::NUM_ENTRIES {
+                    self.write_l1(i, ss.as_l1_entry());
+                }
+                return Ok(SUPERSECTION_SIZE);
+            }
+            if virt % SECTION_SIZE == 0 && phys % SECTION_SIZE == 0 && remaining >= SECTION_SIZE {
+                self.write_l1(idx, L1Section::new_with_addr_and_attrs(phys as u32, attrs));
+                return Ok(SECTION_SIZE);
+            }
+            let table = self.alloc_l2()?;
+            self.write_l1(
+                idx,
+                L1PageTable::new_with_addr(table_addr(table), attrs.domain, false, false)
+                    .with_p_bit(attrs.p_bit)
+                    .as_l1_entry(),
+            );
+        }
+        let L1Kind::PageTable(pt) = self.l1_kind(idx) else {
+            return Err(Error::AlreadyMapped);
+        };
+        if pt.domain() != attrs.domain {
+            return Err(Error::DomainMismatch);
+        }
+        let table = self.find_l2(pt)?;
+        let l2_idx = l2_index(virt);
+        let page_attrs = page_attrs(&attrs);
+        if virt % LARGE_PAGE_SIZE == 0
+            && phys % LARGE_PAGE_SIZE == 0
+            && remaining >= LARGE_PAGE_SIZE
+        {
+            let page = LargePage::new_with_addr_and_attrs(phys as u32, page_attrs);
+            for i in l2_idx..l2_idx + LargePage::NUM_ENTRIES {
+                self.write_l2(table, i, page.as_l2_entry());
+            }
+            return Ok(LARGE_PAGE_SIZE);
+        }
+        let page = SmallPage::new_with_addr_and_attrs(phys as u32, page_attrs);
+        self.write_l2(table, l2_idx, page.as_l2_entry());
+        Ok(SMALL_PAGE_SIZE)
+    }
+
+    /// Unmap (if `attrs` is `None`) or re-protect the given range
+    fn update(
+        &mut self,
+        virt: u32,
+        len: u32,
+        attrs: Option<SectionAttributes>,
+    ) -> Result<(), Error> {
+        let end = virt as u64 + len as u64;
+        let mut addr = virt as u64;
+        while addr < end {
+            let idx = (addr / SECTION_SIZE) as usize;
+            let chunk_end = core::cmp::min((idx as u64 + 1) * SECTION_SIZE, end);
+            match self.l1_kind(idx) {
+                L1Kind::Fault => {
+                    addr = chunk_end;
+                }
+                L1Kind::Supersection(ss) => {
+                    let first = idx & !(Supersection::NUM_ENTRIES - 1);
+                    let start = first as u64 * SECTION_SIZE;
+                    let covered = addr == start && end >= start + SUPERSECTION_SIZE;
+                    match attrs {
+                        Some(a) if covered && a.domain.value() == 0 => {
+                            let new = Supersection::new_with_addr_and_attrs(ss.phys_addr(), a);
+                            for i in first..first + Supersection::NUM_ENTRIES {
+                                self.write_l1(i, new.as_l1_entry());
+                            }
+                            addr = start + SUPERSECTION_SIZE;
+                        }
+                        None if covered => {
+                            for i in first..first + Supersection::NUM_ENTRIES {
+                                self.write_l1(i, L1Section::ZERO);
+                            }
+                            addr = start + SUPERSECTION_SIZE;
+                        }
+                        _ => {
+                            // Split into sections, then look at this entry again
+                            let Ok(base) = u32::try_from(ss.phys_addr()) else {
+                                return Err(Error::PhysAddrTooWide);
+                            };
+                            let section_attrs = ss.section_attrs();
+                            self.break_l1(first, Supersection::NUM_ENTRIES);
+                            for i in 0..Supersection::NUM_ENTRIES {
+                                let phys = base + (i as u64 * SECTION_SIZE) as u32;
+                                self.write_l1(
+                                    first + i,
+                                    L1Section::new_with_addr_and_attrs(phys, section_attrs),
+                                );
+                            }
+                        }
+                    }
+                }
+                L1Kind::Section(section) => {
+                    if addr % SECTION_SIZE == 0 && chunk_end - addr == SECTION_SIZE {
+                        let new = match attrs {
+                            Some(a) => {
+                                let phys = section.base_addr_upper_bits().value() as u32;
+                                L1Section::new_with_addr_and_attrs(phys << 20, a)
+                            }
+                            None => L1Section::ZERO,
+                        };
+                        self.write_l1(idx, new);
+                        addr = chunk_end;
+                    } else {
+                        // Split into large pages, then look at this entry again
+                        self.split_section(idx, section)?;
+                    }
+                }
+                L1Kind::PageTable(pt) => {
+                    let whole = addr % SECTION_SIZE == 0 && chunk_end - addr == SECTION_SIZE;
+                    if let Some(a) = attrs {
+                        if a.domain != pt.domain() {
+                            if !whole {
+                                return Err(Error::DomainMismatch);
+                            }
+                            let new = pt.with_domain(a.domain);
+                            self.write_l1(idx, new.as_l1_entry());
+                        }
+                    }
+                    let table = self.find_l2(pt)?;
+                    self.update_l2(table, addr, chunk_end, attrs.as_ref().map(page_attrs))?;
+                    if attrs.is_none() && self.l2_is_empty(table) {
+                        self.write_l1(idx, L1Section::ZERO);
+                    }
+                    addr = chunk_end;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that [`update`](Self::update) will not fail part way through the range
+    ///
+    /// Every partly covered section needs a table from the pool to split it
+    /// into. Tables which the update would free are not counted, so this can
+    /// fail when the update itself would have just had enough.
+    fn check_update(
+        &self,
+        virt: u32,
+        len: u32,
+        attrs: Option<&SectionAttributes>,
+    ) -> Result<(), Error> {
+        let end = virt as u64 + len as u64;
+        let mut addr = virt as u64;
+        let mut tables_needed = 0;
+        while addr < end {
+            let idx = (addr / SECTION_SIZE) as usize;
+            let chunk_end = core::cmp::min((idx as u64 + 1) * SECTION_SIZE, end);
+            let whole = addr % SECTION_SIZE == 0 && chunk_end - addr == SECTION_SIZE;
+            let domain = match self.l1_kind(idx) {
+                L1Kind::Fault => None,
+                L1Kind::Supersection(ss) => {
+                    let first = idx & !(Supersection::NUM_ENTRIES - 1);
+                    let start = first as u64 * SECTION_SIZE;
+                    let covered = addr == start && end >= start + SUPERSECTION_SIZE;
+                    if covered && attrs.is_none_or(|a| a.domain.value() == 0) {
+                        addr = start + SUPERSECTION_SIZE;
+                        continue;
+                    }
+                    // This will be split into sections
+                    if ss.phys_addr() > u32::MAX as u64 {
+                        return Err(Error::PhysAddrTooWide);
+                    }
+                    if !whole {
+                        tables_needed += 1;
+                    }
+                    Some(ss.section_attrs().domain)
+                }
+                L1Kind::Section(section) => {
+                    let Ok(section_attrs) = section.section_attrs() else {
+                        return Err(Error::UnknownTable);
+                    };
+                    if !whole {
+                        tables_needed += 1;
+                    }
+                    Some(section_attrs.domain)
+                }
+                L1Kind::PageTable(pt) => {
+                    self.find_l2(pt)?;
+                    Some(pt.domain())
+                }
+            };
+            if let (Some(domain), Some(a)) = (domain, attrs) {
+                // Whole sections can move domain, but part of one cannot
+                if a.domain != domain && !whole {
+                    return Err(Error::DomainMismatch);
+                }
+            }
+            addr = chunk_end;
+        }
+        if tables_needed > self.free_tables() {
+            return Err(Error::OutOfTables);
+        }
+        Ok(())
+    }
+
+    /// Unmap or re-protect the part of an L2 table between `addr` and `end`
+    fn update_l2(
+        &mut self,
+        table: &'a L2Table,
+        mut addr: u64,
+        end: u64,
+        attrs: Option<PageAttributes>,
+    ) -> Result<(), Error> {
+        while addr < end {
+            let l2_idx = l2_index(addr);
+            let entry = self.read_l2(table, l2_idx);
+            match entry.entry_type() {
+                L2EntryType::Fault => {
+                    addr += SMALL_PAGE_SIZE;
+                }
+                L2EntryType::SmallPage => {
+                    let new = match attrs {
+                        Some(a) => {
+                            let page = SmallPage::new_with_raw_value(entry.raw_value());
+                            SmallPage::new_with_addr_and_attrs(page.phys_addr(), a).as_l2_entry()
+                        }
+                        None => L2Entry::ZERO,
+                    };
+                    self.write_l2(table, l2_idx, new);
+                    addr += SMALL_PAGE_SIZE;
+                }
+                L2EntryType::LargePage => {
+                    let page = LargePage::new_with_raw_value(entry.raw_value());
+                    let first = l2_idx & !(LargePage::NUM_ENTRIES - 1);
+                    let start = addr & !(LARGE_PAGE_SIZE - 1);
+                    if addr == start && end >= start + LARGE_PAGE_SIZE {
+                        let new = match attrs {
+                            Some(a) => LargePage::new_with_addr_and_attrs(page.phys_addr(), a)
+                                .as_l2_entry(),
+                            None => L2Entry::ZERO,
+                        };
+                        for i in first..first + LargePage::NUM_ENTRIES {
+                            self.write_l2(table, i, new);
+                        }
+                        addr = start + LARGE_PAGE_SIZE;
+                    } else {
+                        // Split into small pages, then look at this entry again
+                        self.break_l2(table, first, LargePage::NUM_ENTRIES);
+                        for i in 0..LargePage::NUM_ENTRIES {
+                            let phys = page.phys_addr() + (i as u64 * SMALL_PAGE_SIZE) as u32;
+                            let small = SmallPage::new_with_addr_and_attrs(phys, page.page_attrs());
+                            self.write_l2(table, first + i, small.as_l2_entry());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace a section with an L2 table of equivalent large pages
+    fn split_section(&mut self, idx: usize, section: L1Section) -> Result<(), Error> {
+        let Ok(attrs) = section.section_attrs() else {
+            return Err(Error::UnknownTable);
+        };
+        let table = self.alloc_l2()?;
+        let base = (section.base_addr_upper_bits().value() as u32) << 20;
+        for i in 0..NUM_L2_PAGE_TABLE_ENTRIES / LargePage::NUM_ENTRIES {
+            let page = LargePage::new_with_addr_and_attrs(
+                base + (i as u64 * LARGE_PAGE_SIZE) as u32,
+                page_attrs(&attrs),
+            );
+            for j in 0..LargePage::NUM_ENTRIES {
+                self.write_l2(table, i * LargePage::NUM_ENTRIES + j, page.as_l2_entry());
+            }
+        }
+        self.break_l1(idx, 1);
+        self.write_l1(
+            idx,
+            L1PageTable::new_with_addr(table_addr(table), attrs.domain, false, false)
+                .with_p_bit(attrs.p_bit)
+                .as_l1_entry(),
+        );
+        Ok(())
+    }
+
+    /// Is every page in the range unmapped?
+    fn is_unmapped(&self, virt: u32, len: u32) -> Result<bool, Error> {
+        self.all_pages(virt, len, false)
+    }
+
+    /// Is every page in the range mapped?
+    fn is_fully_mapped(&self, virt: u32, len: u32) -> Result<bool, Error> {
+        self.all_pages(virt, len, true)
+    }
+
+    /// Check whether every page in the range is (or is not) mapped
+    fn all_pages(&self, virt: u32, len: u32, mapped: bool) -> Result<bool, Error> {
+        let end = virt as u64 + len as u64;
+        let mut addr = virt as u64;
+        while addr < end {
+            let idx = (addr / SECTION_SIZE) as usize;
+            let chunk_end = core::cmp::min((idx as u64 + 1) * SECTION_SIZE, end);
+            match self.l1_kind(idx) {
+                L1Kind::Fault if mapped => return Ok(false),
+                L1Kind::Section(_) | L1Kind::Supersection(_) if !mapped => return Ok(false),
+                L1Kind::PageTable(pt) => {
+                    let table = self.find_l2(pt)?;
+                    for i in l2_index(addr)..=l2_index(chunk_end - 1) {
+                        let is_mapped = self.read_l2(table, i).entry_type() != L2EntryType::Fault;
+                        if is_mapped != mapped {
+                            return Ok(false);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            addr = chunk_end;
+        }
+        Ok(true)
+    }
+
+    /// Find a table in the pool which no L1 entry points at, and clear it
+    fn alloc_l2(&mut self) -> Result<&'a L2Table, Error> {
+        let pool = self.pool;
+        for table in pool {
+            if !self.in_use(table) {
+                for i in 0..NUM_L2_PAGE_TABLE_ENTRIES {
+                    self.write_l2(table, i, L2Entry::ZERO);
+                }
+                return Ok(table);
+            }
+        }
+        Err(Error::OutOfTables)
+    }
+
+    /// How many tables in the pool no L1 entry points at
+    fn free_tables(&self) -> usize {
+        self.pool.iter().filter(|t| !self.in_use(t)).count()
+    }
+
+    /// Does any L1 entry point at this table?
+    fn in_use(&self, table: &L2Table) -> bool {
+        let addr = table_addr(table);
+        (0..super::NUM_L1_PAGE_TABLE_ENTRIES)
+            .any(|i| matches!(self.l1_kind(i), L1Kind::PageTable(pt) if pt.l2_table_addr() == addr))
+    }
+
+    /// Find the pool table that an L1 entry points at
+    fn find_l2(&self, pt: L1PageTable) -> Result<&'a L2Table, Error> {
+        self.pool
+            .iter()
+            .find(|t| table_addr(t) == pt.l2_table_addr())
+            .ok_or(Error::UnknownTable)
+    }
+
+    fn l2_is_empty(&self, table: &L2Table) -> bool {
+        (0..NUM_L2_PAGE_TABLE_ENTRIES)
+            .all(|i| self.read_l2(table, i).entry_type() == L2EntryType::Fault)
+    }
+
+    fn l1_kind(&self, idx: usize) -> L1Kind {
+        let entry = self.read_l1(idx);
+        let raw = entry.raw_value();
+        match entry.entry_type() {
+            L1EntryType::Fault => L1Kind::Fault,
+            L1EntryType::PageTable => L1Kind::PageTable(L1PageTable::new_with_raw_value(raw)),
+            _ if raw & (1 << 18) != 0 => {
+                L1Kind::Supersection(Supersection::new_with_raw_value(raw))
+            }
+            _ => L1Kind::Section(entry),
+        }
+    }
+
+    fn read_l1(&self, idx: usize) -> L1Section {
+        // Safety: the caller of `new` promised nobody else is modifying this table
+        unsafe {
+            (self.l1.entries.get() as *const L1Section)
+                .add(idx)
+                .read_volatile()
+        }
+    }
+
+    fn write_l1(&mut self, idx: usize, entry: L1Section) {
+        // Safety: the caller of `new` promised nobody else is modifying this table
+        unsafe {
+            let p = (self.l1.entries.get() as *mut L1Section).add(idx);
+            p.write_volatile(entry);
+            clean_descriptor(p as usize);
+        }
+    }
+
+    fn read_l2(&self, table: &L2Table, idx: usize) -> L2Entry {
+        // Safety: the caller of `new` promised nobody else is modifying this table
+        unsafe {
+            (table.entries.get() as *const L2Entry)
+                .add(idx)
+                .read_volatile()
+        }
+    }
+
+    fn write_l2(&mut self, table: &L2Table, idx: usize, entry: L2Entry) {
+        // Safety: the caller of `new` promised nobody else is modifying this table
+        unsafe {
+            let p = (table.entries.get() as *mut L2Entry).add(idx);
+            p.write_volatile(entry);
+            clean_descriptor(p as usize);
+        }
+    }
+
+    /// Clear `count` L1 entries from `first`, and make sure no TLB still holds them
+    ///
+    /// This is the break in break-before-make, for replacing live entries
+    /// with ones of a different size.
+    fn break_l1(&mut self, first: usize, count: usize) {
+        for i in first..first + count {
+            self.write_l1(i, L1Section::ZERO);
+        }
+        invalidate_tlb();
+    }
+
+    /// Clear `count` entries of an L2 table from `first`, and make sure no TLB still holds them
+    fn break_l2(&mut self, table: &L2Table, first: usize, count: usize) {
+        for i in first..first + count {
+            self.write_l2(table, i, L2Entry::ZERO);
+        }
+        invalidate_tlb();
+    }
+
+    /// Make the table changes visible to the MMU
+    fn sync(&self) {
+        invalidate_tlb();
+    }
+}
+
+/// Invalidate the TLBs and branch predictors, after a DSB for the table writes
+///
+/// With the Multiprocessing Extensions this covers every processor in the
+/// Inner Shareable domain, not just this one.
+fn invalidate_tlb() {
+    #[cfg(arm_architecture = "v7-a")]
+    {
+        use crate::register::{BpIAll, BpIAllIs, Mpidr, TlbIAll, TlbIAllIs};
+
+        crate::asm::dsb();
+        if Mpidr::read().mp_extensions() {
+            TlbIAllIs::write();
+            BpIAllIs::write();
+        } else {
+            TlbIAll::write();
+            BpIAll::write();
+        }
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+}
+
+/// Clean a modified descriptor out to memory, in case table walks don't look in the cache
+fn clean_descriptor(_addr: usize) {
+    #[cfg(arm_architecture = "v7-a")]
+    crate::cache::clean_data_cache_line_to_poc(_addr as u32);
+}
+
+fn check_range(addr: u32, len: u32) -> Result<(), Error> {
+    if addr as u64 % SMALL_PAGE_SIZE != 0 || len as u64 % SMALL_PAGE_SIZE != 0 {
+        return Err(Error::Unaligned);
+    }
+    if len == 0 || addr.checked_add(len - 1).is_none() {
+        return Err(Error::InvalidRange);
+    }
+    Ok(())
+}
+
+fn table_addr(table: &L2Table) -> u32 {
+    core::ptr::addr_of!(*table) as usize as u32
+}
+
+fn l2_index(addr: u64) -> usize {
+    ((addr / SMALL_PAGE_SIZE) as usize) % NUM_L2_PAGE_TABLE_ENTRIES
+}
+
+fn page_attrs(attrs: &SectionAttributes) -> PageAttributes {
+    PageAttributes {
+        non_global: attrs.non_global,
+        shareable: attrs.shareable,
+        access: attrs.access,
+        memory_attrs: attrs.memory_attrs,
+        execute_never: attrs.execute_never,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mmu::test_util::zeroed;
+    use crate::mmu::{AccessPermissions, MemoryRegionAttributes, NUM_L1_PAGE_TABLE_ENTRIES};
+    use arbitrary_int::u4;
+    use std::boxed::Box;
+
+    const ATTRS: SectionAttributes = SectionAttributes {
+        non_global: false,
+        p_bit: false,
+        shareable: true,
+        access: AccessPermissions::FullAccess,
+        memory_attrs: MemoryRegionAttributes::OuterAndInnerWriteBackWriteAlloc.as_raw(),
+        domain: u4::new(0),
+        execute_never: false,
+    };
+
+    const RO_ATTRS: SectionAttributes = SectionAttributes {
+        access: AccessPermissions::ReadOnly,
+        ..ATTRS
+    };
+
+    fn entry(l1: &L1Table, idx: usize) -> L1Section {
+        unsafe { (*l1.entries.get())[idx] }
+    }
+
+    fn page(l2: &L2Table, idx: usize) -> L2Entry {
+        unsafe { (*l2.entries.get())[idx] }
+    }
+
+    #[test]
+    fn map_picks_largest_entries() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 2]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        // 16 MiB + 1 MiB + 64 KiB + 4 KiB
+        let len = 0x0111_1000;
+        mapper.map(0x1000_0000, 0x8000_0000, len, ATTRS).unwrap();
+        let ss = Supersection::from_l1_entry(entry(&l1, 0x100)).unwrap();
+        assert_eq!(ss.phys_addr(), 0x8000_0000);
+        assert_eq!(entry(&l1, 0x10F), entry(&l1, 0x100));
+        assert_eq!(
+            entry(&l1, 0x110),
+            L1Section::new_with_addr_and_attrs(0x8100_0000, ATTRS)
+        );
+        let pt = L1PageTable::from_l1_entry(entry(&l1, 0x111)).unwrap();
+        assert_eq!(pt.l2_table_addr(), table_addr(&pool[0]));
+        assert_eq!(page(&pool[0], 0).entry_type(), L2EntryType::LargePage);
+        assert_eq!(page(&pool[0], 15).entry_type(), L2EntryType::LargePage);
+        assert_eq!(page(&pool[0], 16).entry_type(), L2EntryType::SmallPage);
+        assert_eq!(page(&pool[0], 17).entry_type(), L2EntryType::Fault);
+
+        assert_eq!(
+            mapper.map(0x1011_0000, 0x9000_0000, 0x1000, ATTRS),
+            Err(Error::AlreadyMapped)
+        );
+        assert_eq!(
+            mapper.map(0x2000_0000, 0x9000_0800, 0x1000, ATTRS),
+            Err(Error::Unaligned)
+        );
+
+        // Unmapping everything puts the L2 table back in the pool
+        mapper.unmap(0x1000_0000, len).unwrap();
+        assert!((0..NUM_L1_PAGE_TABLE_ENTRIES).all(|i| entry(&l1, i) == L1Section::ZERO));
+    }
+
+    #[test]
+    fn partial_unmap_and_protect_split_entries() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 1]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        mapper
+            .map(0x0000_0000, 0x0000_0000, 0x0020_0000, ATTRS)
+            .unwrap();
+
+        // Punch a guard page into the first section
+        mapper.unmap(0x0001_0000, 0x1000).unwrap();
+        let pt = L1PageTable::from_l1_entry(entry(&l1, 0)).unwrap();
+        assert_eq!(pt.l2_table_addr(), table_addr(&pool[0]));
+        assert_eq!(page(&pool[0], 0).entry_type(), L2EntryType::LargePage);
+        assert_eq!(page(&pool[0], 16).entry_type(), L2EntryType::Fault);
+        let next = SmallPage::from_l2_entry(page(&pool[0], 17)).unwrap();
+        assert_eq!(next.phys_addr(), 0x0001_1000);
+
+        // Protecting the guard page fails, and we have no tables left to split the second section
+        assert_eq!(
+            mapper.protect(0x0001_0000, 0x1000, RO_ATTRS),
+            Err(Error::NotMapped)
+        );
+        assert_eq!(
+            mapper.protect(0x0010_0000, 0x1000, RO_ATTRS),
+            Err(Error::OutOfTables)
+        );
+
+        // Protecting the whole second section needs no table
+        mapper.protect(0x0010_0000, 0x0010_0000, RO_ATTRS).unwrap();
+        assert_eq!(
+            entry(&l1, 1).section_attrs().unwrap().access,
+            AccessPermissions::ReadOnly
+        );
+    }
+
+    #[test]
+    fn wide_supersection_is_not_split() {
+        let l1: Box<L1Table> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &[]) };
+        let ss = Supersection::new_with_addr_and_attrs(0x12_0000_0000, ATTRS);
+        for i in 0..Supersection::NUM_ENTRIES {
+            mapper.write_l1(i, ss.as_l1_entry());
+        }
+        assert_eq!(
+            mapper.unmap(0x0010_0000, 0x0010_0000),
+            Err(Error::PhysAddrTooWide)
+        );
+        assert_eq!(
+            mapper.protect(0, 0x0010_0000, RO_ATTRS),
+            Err(Error::PhysAddrTooWide)
+        );
+        assert!((0..Supersection::NUM_ENTRIES).all(|i| entry(&l1, i) == ss.as_l1_entry()));
+        // Covering the whole supersection needs no split
+        mapper.protect(0, 0x0100_0000, RO_ATTRS).unwrap();
+        let ss = Supersection::from_l1_entry(entry(&l1, 0)).unwrap();
+        assert_eq!(ss.phys_addr(), 0x12_0000_0000);
+    }
+
+    #[test]
+    fn failed_update_changes_nothing() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 1]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        mapper
+            .map(0x0000_0000, 0x0000_0000, 0x0030_0000, ATTRS)
+            .unwrap();
+        let before: std::vec::Vec<L1Section> = (0..NUM_L1_PAGE_TABLE_ENTRIES)
+            .map(|i| entry(&l1, i))
+            .collect();
+        let unchanged =
+            |l1: &L1Table| (0..NUM_L1_PAGE_TABLE_ENTRIES).all(|i| entry(l1, i) == before[i]);
+
+        // Two partly covered sections need two tables, but the pool has one
+        assert_eq!(mapper.unmap(0x000F_F000, 0x2000), Err(Error::OutOfTables));
+        assert!(unchanged(&l1));
+        assert_eq!(
+            mapper.protect(0x000F_F000, 0x2000, RO_ATTRS),
+            Err(Error::OutOfTables)
+        );
+        assert!(unchanged(&l1));
+
+        // The first section can change domain, but part of the second cannot
+        let other_domain = SectionAttributes {
+            domain: u4::new(3),
+            ..RO_ATTRS
+        };
+        assert_eq!(
+            mapper.protect(0x0000_0000, 0x0010_1000, other_domain),
+            Err(Error::DomainMismatch)
+        );
+        assert!(unchanged(&l1));
+    }
+
+    #[test]
+    fn page_tables_keep_p_bit() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 2]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        let attrs = SectionAttributes {
+            p_bit: true,
+            ..ATTRS
+        };
+        mapper.map(0x0000_0000, 0x0000_0000, 0x1000, attrs).unwrap();
+        mapper
+            .map(0x0010_0000, 0x0010_0000, 0x0010_0000, attrs)
+            .unwrap();
+        mapper.unmap(0x0010_0000, 0x1000).unwrap();
+        for idx in [0, 1] {
+            assert!(L1PageTable::from_l1_entry(entry(&l1, idx)).unwrap().p_bit());
+        }
+    }
+}
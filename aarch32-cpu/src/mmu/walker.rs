@@ -0,0 +1,319 @@
+//! Walks Short-descriptor translation tables and describes what they map
+//!
+//! The [`Walker`] visits every valid entry in an [`L1Table`] (and any L2
+//! tables it points to) in virtual address order, and merges neighbouring
+//! entries which are virtually and physically contiguous and have identical
+//! attributes into a single [`MappedRange`].
+
+use super::{
+    AccessPermissions, L1EntryType, L1PageTable, L1Section, L1Table, L2Entry, L2EntryType, L2Table,
+    LargePage, SectionAttributes, SmallPage, Supersection, NUM_L1_PAGE_TABLE_ENTRIES,
+    NUM_L2_PAGE_TABLE_ENTRIES,
+};
+
+const SMALL_PAGE_SIZE: u64 = 4 * 1024;
+const LARGE_PAGE_SIZE: u64 = 64 * 1024;
+const SECTION_SIZE: u64 = 1024 * 1024;
+const SUPERSECTION_SIZE: u64 = 16 * 1024 * 1024;
+
+/// A contiguous range of virtual memory with the same attributes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MappedRange {
+    /// Start of the range in the virtual address space
+    pub virt: u32,
+    /// Start of the range in the physical address space (up to 40 bits)
+    pub phys: u64,
+    /// Length of the range in bytes
+    pub len: u64,
+    /// Attributes of the range
+    ///
+    /// For pages, the domain and P bit come from the L1 entry pointing to the
+    /// L2 table.
+    pub attrs: SectionAttributes,
+}
+
+impl core::fmt::Display for MappedRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let access = match self.attrs.access {
+            AccessPermissions::PermissionFault => "--/--",
+            AccessPermissions::PrivilegedOnly => "rw/--",
+            AccessPermissions::NoUserWrite => "rw/r-",
+            AccessPermissions::FullAccess => "rw/rw",
+            AccessPermissions::PrivilegedReadOnly => "r-/--",
+            AccessPermissions::ReadOnly | AccessPermissions::_Reserved2 => "r-/r-",
+            AccessPermissions::_Reserved1 => "??/??",
+        };
+        let mem = &self.attrs.memory_attrs;
+        write!(
+            f,
+            "{:#010x}..{:#010x} -> {:#012x} {} {}{}{} tex={:03b} c={} b={} domain={}",
+            self.virt,
+            self.virt as u64 + self.len,
+            self.phys,
+            access,
+            if self.attrs.execute_never { "xn" } else { "x " },
+            if self.attrs.shareable { " s" } else { "  " },
+            if self.attrs.non_global { " ng" } else { "   " },
+            mem.type_extensions,
+            mem.c as u8,
+            mem.b as u8,
+            self.attrs.domain,
+        )
+    }
+}
+
+/// How the walker finds the L2 table that an L1 entry points to
+enum TableLookup<'a> {
+    /// Dereference the physical address as if it were a virtual address
+    Identity,
+    /// Search a slice of tables for one at the right address
+    Pool(&'a [L2Table]),
+}
+
+/// Iterates over the [`MappedRange`]s in a set of translation tables
+pub struct Walker<'a> {
+    l1: &'a L1Table,
+    lookup: TableLookup<'a>,
+    l1_idx: usize,
+    l2_idx: usize,
+    pending: Option<MappedRange>,
+}
+
+impl<'a> Walker<'a> {
+    /// Walk an L1 table, finding L2 tables in `tables`
+    ///
+    /// L1 entries pointing at L2 tables which are not in `tables` are skipped.
+    pub fn new(l1: &'a L1Table, tables: &'a [L2Table]) -> Walker<'a> {
+        Walker {
+            l1,
+            lookup: TableLookup::Pool(tables),
+            l1_idx: 0,
+            l2_idx: 0,
+            pending: None,
+        }
+    }
+
+    /// Walk an L1 table, following the addresses of L2 tables directly
+    ///
+    /// # Safety
+    ///
+    /// Every L2 table referenced by `l1` must be identity mapped, and must
+    /// live for at least `'a`.
+    pub unsafe fn new_identity_mapped(l1: &'a L1Table) -> Walker<'a> {
+        Walker {
+            l1,
+            lookup: TableLookup::Identity,
+            l1_idx: 0,
+            l2_idx: 0,
+            pending: None,
+        }
+    }
+
+    fn find_l2(&self, pt: L1PageTable) -> Option<&'a L2Table> {
+        match self.lookup {
+            TableLookup::Identity => {
+                // Safety: the caller of `new_identity_mapped` said this was OK
+                Some(unsafe { &*(pt.l2_table_addr() as usize as *const L2Table) })
+            }
+            TableLookup::Pool(tables) => tables
+                .iter()
+                .find(|t| core::ptr::addr_of!(**t) as usize as u32 == pt.l2_table_addr()),
+        }
+    }
+
+    /// Find the next valid entry, as an un-merged range
+    fn next_entry(&mut self) -> Option<MappedRange> {
+        while self.l1_idx < NUM_L1_PAGE_TABLE_ENTRIES {
+            let idx = self.l1_idx;
+            let virt = (idx as u64 * SECTION_SIZE) as u32;
+            // Safety: we only read from the table
+            let entry: L1Section = unsafe { (*self.l1.entries.get())[idx] };
+            let raw = entry.raw_value();
+            match entry.entry_type() {
+                L1EntryType::Fault => {
+                    self.l1_idx += 1;
+                }
+                L1EntryType::PageTable => {
+                    let pt = L1PageTable::new_with_raw_value(raw);
+                    let Some(table) = self.find_l2(pt) else {
+                        self.l1_idx += 1;
+                        continue;
+                    };
+                    if let Some(range) = self.next_page(table, pt, virt) {
+                        return Some(range);
+                    }
+                    self.l1_idx += 1;
+                    self.l2_idx = 0;
+                }
+                _ if raw & (1 << 18) != 0 => {
+                    let ss = Supersection::new_with_raw_value(raw);
+                    // Skip the rest of the 16 replicated entries
+                    self.l1_idx = (idx | (Supersection::NUM_ENTRIES - 1)) + 1;
+                    return Some(MappedRange {
+                        virt: virt & !(SUPERSECTION_SIZE as u32 - 1),
+                        phys: ss.phys_addr(),
+                        len: SUPERSECTION_SIZE,
+                        attrs: ss.section_attrs(),
+                    });
+                }
+                _ => {
+                    self.l1_idx += 1;
+                    let Ok(attrs) = entry.section_attrs() else {
+                        continue;
+                    };
+                    return Some(MappedRange {
+                        virt,
+                        phys: (entry.base_addr_upper_bits().value() as u64) << 20,
+                        len: SECTION_SIZE,
+                        attrs,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Find the next valid page in an L2 table
+    fn next_page(&mut self, table: &L2Table, pt: L1PageTable, virt: u32) -> Option<MappedRange> {
+        while self.l2_idx < NUM_L2_PAGE_TABLE_ENTRIES {
+            let idx = self.l2_idx;
+            // Safety: we only read from the table
+            let entry: L2Entry = unsafe { (*table.entries.get())[idx] };
+            let page_virt = virt + (idx as u64 * SMALL_PAGE_SIZE) as u32;
+            let (phys, len, page_attrs) = match entry.entry_type() {
+                L2EntryType::Fault => {
+                    self.l2_idx += 1;
+                    continue;
+                }
+                L2EntryType::SmallPage => {
+                    self.l2_idx += 1;
+                    let page = SmallPage::new_with_raw_value(entry.raw_value());
+                    (page.phys_addr(), SMALL_PAGE_SIZE, page.page_attrs())
+                }
+                L2EntryType::LargePage => {
+                    // Skip the rest of the 16 replicated entries
+                    self.l2_idx = (idx | (LargePage::NUM_ENTRIES - 1)) + 1;
+                    let page = LargePage::new_with_raw_value(entry.raw_value());
+                    (page.phys_addr(), LARGE_PAGE_SIZE, page.page_attrs())
+                }
+            };
+            let virt_mask = !(len as u32 - 1);
+            return Some(MappedRange {
+                virt: page_virt & virt_mask,
+                phys: phys as u64,
+                len,
+                attrs: SectionAttributes {
+                    non_global: page_attrs.non_global,
+                    p_bit: pt.p_bit(),
+                    shareable: page_attrs.shareable,
+                    access: page_attrs.access,
+                    memory_attrs: page_attrs.memory_attrs,
+                    domain: pt.domain(),
+                    execute_never: page_attrs.execute_never,
+                },
+            });
+        }
+        None
+    }
+}
+
+impl Iterator for Walker<'_> {
+    type Item = MappedRange;
+
+    fn next(&mut self) -> Option<MappedRange> {
+        loop {
+            let Some(entry) = self.next_entry() else {
+                return self.pending.take();
+            };
+            match self.pending.as_mut() {
+                Some(pending)
+                    if pending.virt as u64 + pending.len == entry.virt as u64
+                        && pending.phys + pending.len == entry.phys
+                        && pending.attrs == entry.attrs =>
+                {
+                    pending.len += entry.len;
+                }
+                Some(_) => return self.pending.replace(entry),
+                None => self.pending = Some(entry),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mmu::test_util::zeroed;
+    use crate::mmu::{mapper::Mapper, MemoryRegionAttributes};
+    use arbitrary_int::u4;
+    use std::{boxed::Box, format, vec::Vec};
+
+    const ATTRS: SectionAttributes = SectionAttributes {
+        non_global: false,
+        p_bit: false,
+        shareable: true,
+        access: AccessPermissions::FullAccess,
+        memory_attrs: MemoryRegionAttributes::OuterAndInnerWriteBackWriteAlloc.as_raw(),
+        domain: u4::new(0),
+        execute_never: false,
+    };
+
+    const DEVICE_ATTRS: SectionAttributes = SectionAttributes {
+        shareable: false,
+        memory_attrs: MemoryRegionAttributes::ShareableDevice.as_raw(),
+        execute_never: true,
+        ..ATTRS
+    };
+
+    #[test]
+    fn coalesces_mixed_entries() {
+        let l1: Box<L1Table> = zeroed();
+        let pool: Box<[L2Table; 2]> = zeroed();
+        let mut mapper = unsafe { Mapper::new(&l1, &pool[..]) };
+        // A supersection, a section, a large page and a small page, all contiguous
+        mapper
+            .map(0x1000_0000, 0x8000_0000, 0x0111_1000, ATTRS)
+            .unwrap();
+        // A device page with a guard page before it
+        mapper
+            .map(0x1111_3000, 0x1011_3000, 0x1000, DEVICE_ATTRS)
+            .unwrap();
+
+        let ranges: Vec<MappedRange> = Walker::new(&l1, &pool[..]).collect();
+        assert_eq!(
+            ranges,
+            [
+                MappedRange {
+                    virt: 0x1000_0000,
+                    phys: 0x8000_0000,
+                    len: 0x0111_1000,
+                    attrs: ATTRS,
+                },
+                MappedRange {
+                    virt: 0x1111_3000,
+                    phys: 0x1011_3000,
+                    len: 0x1000,
+                    attrs: DEVICE_ATTRS,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        let range = MappedRange {
+            virt: 0x1000_0000,
+            phys: 0x8000_0000,
+            len: 0x0010_0000,
+            attrs: DEVICE_ATTRS,
+        };
+        assert_eq!(
+            format!("{}", range),
+            "0x10000000..0x10100000 -> 0x0080000000 rw/rw xn      tex=000 c=0 b=1 domain=0"
+        );
+    }
+}
@@ -8,6 +8,11 @@
 use crate::register;
 
 use arbitrary_int::{u2, u3};
+
+pub mod evaluate;
+pub mod planner;
+#[doc(inline)]
+pub use register::dracr::AccessPerms;
 #[doc(inline)]
 pub use register::drsr::RegionSize;
 
@@ -19,6 +24,8 @@ pub enum Error {
     TooManyRegions,
     /// Found a region with invalid alignment
     UnalignedRegion(*mut u8),
+    /// Found an empty or backwards address range
+    InvalidRange,
 }
 
 /// Represents our PMSAv7 MPU
@@ -75,6 +82,7 @@ impl Mpu {
             subregion_mask: rsr.subregion_mask(),
             enabled: rsr.enabled(),
             no_exec: racr.nx(),
+            access: racr.ap(),
             mem_attr,
         })
     }
@@ -103,6 +111,7 @@ impl Mpu {
             subregion_mask: rsr.subregion_mask(),
             enabled: rsr.enabled(),
             no_exec: racr.nx(),
+            access: racr.ap(),
             mem_attr,
         })
     }
@@ -136,7 +145,7 @@ impl Mpu {
                 out.set_b(mem_attr_bits.b);
                 out.set_s(mem_attr_bits.s);
                 out.set_nx(region.no_exec);
-                // out.with_ap(region.access_perms);
+                out.set_ap(region.access);
                 out
             });
         }
@@ -161,7 +170,7 @@ impl Mpu {
                 out.set_b(mem_attr_bits.b);
                 out.set_s(mem_attr_bits.s);
                 out.set_nx(region.no_exec);
-                // out.with_ap(region.access_perms);
+                out.set_ap(region.access);
                 out
             });
         }
@@ -221,6 +230,8 @@ pub struct Region {
     pub enabled: bool,
     /// No-Execute in this region
     pub no_exec: bool,
+    /// Who can read and write this region
+    pub access: AccessPerms,
     /// Attributes for this region
     pub mem_attr: MemAttr,
 }
@@ -416,4 +427,14 @@ mod test {
         let mem_attr2 = mem_attr_bits.decode();
         assert_eq!(Some(mem_attr), mem_attr2);
     }
+
+    #[test]
+    fn access_perms() {
+        let racr = register::Dracr::new_with_raw_value(0)
+            .with_ap(AccessPerms::NoUserWrite)
+            .with_nx(true);
+        assert_eq!(racr.raw_value(), 0x0000_1200);
+        let racr = register::Iracr::new_with_raw_value(0x0000_0600);
+        assert_eq!(racr.ap(), AccessPerms::ReadOnly);
+    }
 }
@@ -0,0 +1,192 @@
+//! Evaluates accesses against a PMSAv7 MPU configuration
+//!
+//! When regions overlap, the highest numbered region which matches wins.
+//! Instruction fetches use the instruction regions if there are any, and the
+//! data regions otherwise (as on an MPU with a unified memory map).
+
+use crate::evaluate::{Access, AccessType, Evaluate, Fault, Matched, Outcome, Permissions};
+
+use super::{AccessPerms, Config, MemAttr, Region};
+
+/// The smallest region which can be split into subregions
+const MIN_SUBREGION_REGION_SIZE: u64 = 256;
+
+impl Region {
+    /// Does this region apply to `address`?
+    ///
+    /// Disabled regions, and addresses in disabled subregions, do not match.
+    pub fn contains(&self, address: u32) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let size = 1u64 << (self.size.raw_value().value() + 1);
+        let base = self.base as usize as u64 & !(size - 1);
+        let address = address as u64;
+        if address < base || address >= base + size {
+            return false;
+        }
+        if size >= MIN_SUBREGION_REGION_SIZE {
+            let subregion = (address - base) / (size / 8);
+            return self.subregion_mask & (1 << subregion) == 0;
+        }
+        true
+    }
+}
+
+/// What PL1 and PL0 may read and write, given a region's AP bits
+///
+/// Returns (privileged read, privileged write, user read, user write).
+/// PMSAv7 makes AP\[2:0\] = 0b100 and 0b111 UNPREDICTABLE, so both are
+/// treated as no access.
+const fn access_permissions(access: AccessPerms) -> (bool, bool, bool, bool) {
+    match access {
+        AccessPerms::NoAccess | AccessPerms::_Reserved1 | AccessPerms::_Reserved2 => {
+            (false, false, false, false)
+        }
+        AccessPerms::PrivilegedOnly => (true, true, false, false),
+        AccessPerms::NoUserWrite => (true, true, true, false),
+        AccessPerms::FullAccess => (true, true, true, true),
+        AccessPerms::PrivilegedReadOnly => (true, false, false, false),
+        AccessPerms::ReadOnly => (true, false, true, false),
+    }
+}
+
+impl Evaluate for Config<'_> {
+    type Attributes = Matched<MemAttr>;
+
+    /// Work out what happens when `access` is made to `address`
+    ///
+    /// The background region gives privileged accesses full access; the
+    /// execute-never parts of the default memory map are not modelled.
+    fn evaluate(&self, address: u32, access: Access) -> Result<Outcome<Matched<MemAttr>>, Fault> {
+        let regions = if access.kind == AccessType::Execute && !self.iregions.is_empty() {
+            self.iregions
+        } else {
+            self.dregions
+        };
+        let Some((index, region)) = regions
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, r)| r.contains(address))
+        else {
+            if access.privileged && self.background_config {
+                return Ok(Outcome {
+                    physical_address: address as u64,
+                    attributes: Matched::Background,
+                    permissions: Permissions::ALL,
+                });
+            }
+            return Err(Fault::Background);
+        };
+        let (pr, pw, ur, uw) = access_permissions(region.access);
+        let permissions = if access.privileged {
+            Permissions::new(pr, pw, region.no_exec)
+        } else {
+            Permissions::new(ur, uw, region.no_exec)
+        };
+        if !permissions.allows(access.kind) {
+            return Err(Fault::MpuPermission);
+        }
+        Ok(Outcome {
+            physical_address: address as u64,
+            attributes: Matched::Region {
+                index,
+                attributes: region.mem_attr.clone(),
+            },
+            permissions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pmsav7::RegionSize;
+
+    const RAM: Region = Region {
+        base: 0x2000_0000 as *mut u8,
+        size: RegionSize::_64K,
+        subregion_mask: 0x80,
+        enabled: true,
+        no_exec: true,
+        access: AccessPerms::FullAccess,
+        mem_attr: MemAttr::WriteBackWriteAllocate { shareable: false },
+    };
+
+    const STACK_GUARD: Region = Region {
+        base: 0x2000_0000 as *mut u8,
+        size: RegionSize::_32B,
+        subregion_mask: 0,
+        enabled: true,
+        no_exec: true,
+        access: AccessPerms::NoAccess,
+        mem_attr: MemAttr::StronglyOrdered,
+    };
+
+    #[test]
+    fn highest_region_wins() {
+        let regions = [RAM, STACK_GUARD];
+        let config = Config {
+            background_config: false,
+            iregions: &[],
+            dregions: &regions,
+        };
+        assert_eq!(
+            config.evaluate(0x2000_0020, Access::USER_WRITE),
+            Ok(Outcome {
+                physical_address: 0x2000_0020,
+                attributes: Matched::Region {
+                    index: 0,
+                    attributes: MemAttr::WriteBackWriteAllocate { shareable: false },
+                },
+                permissions: Permissions::new(true, true, true),
+            })
+        );
+        assert_eq!(
+            config.evaluate(0x2000_0000, Access::PRIVILEGED_READ),
+            Err(Fault::MpuPermission)
+        );
+        assert_eq!(
+            config.evaluate(0x2000_0100, Access::USER_EXECUTE),
+            Err(Fault::MpuPermission)
+        );
+    }
+
+    #[test]
+    fn subregions_and_background() {
+        let regions = [RAM];
+        let config = Config {
+            background_config: true,
+            iregions: &[],
+            dregions: &regions,
+        };
+        // The top 8 KiB is in a disabled subregion
+        assert_eq!(
+            config
+                .evaluate(0x2000_E000, Access::PRIVILEGED_EXECUTE)
+                .map(|o| o.attributes),
+            Ok(Matched::Background)
+        );
+        assert_eq!(
+            config.evaluate(0x2000_E000, Access::USER_READ),
+            Err(Fault::Background)
+        );
+    }
+
+    #[test]
+    fn reserved_access_permissions() {
+        for access in [AccessPerms::_Reserved1, AccessPerms::_Reserved2] {
+            let regions = [Region { access, ..RAM }];
+            let config = Config {
+                background_config: false,
+                iregions: &[],
+                dregions: &regions,
+            };
+            assert_eq!(
+                config.evaluate(0x2000_0000, Access::PRIVILEGED_READ),
+                Err(Fault::MpuPermission)
+            );
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Fits arbitrary address ranges into PMSAv7 MPU regions
+//!
+//! A PMSAv7 region must be a power of two in size and aligned to its size,
+//! but regions of 256 bytes or more are split into eight subregions which can
+//! be disabled individually. The [`RegionPlanner`] uses that to cover a range
+//! like `__stext..__etext` with as few regions as it can, taking the region
+//! which reaches furthest through the range at each step.
+
+use core::ops::Range;
+
+use arbitrary_int::u5;
+
+use super::{AccessPerms, Error, MemAttr, Region, RegionSize};
+
+/// The smallest region the MPU supports
+const MIN_REGION_SIZE: u64 = 32;
+
+/// The smallest region which can be split into subregions
+const MIN_SUBREGION_REGION_SIZE: u64 = 256;
+
+/// The end of the 32-bit address space
+const ADDRESS_SPACE_END: u64 = 1 << 32;
+
+/// The attributes given to every region from a [`RegionPlanner`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionAttributes {
+    /// No-Execute in this region
+    pub no_exec: bool,
+    /// Who can read and write this region
+    pub access: AccessPerms,
+    /// Memory ordering and cacheability of this region
+    pub mem_attr: MemAttr,
+}
+
+/// Produces the [`Region`]s which cover an address range
+///
+/// This is an iterator, so the regions can be collected into whatever storage
+/// suits (or counted, with `planner.clone().count()`).
+#[derive(Clone, Debug)]
+pub struct RegionPlanner {
+    attrs: RegionAttributes,
+    requested: Range<u64>,
+    covered: Range<u64>,
+    next: u64,
+}
+
+impl RegionPlanner {
+    /// Plan the regions for the given range of addresses
+    ///
+    /// The MPU works in units of 32 bytes, so if `range` does not start and
+    /// end on a 32 byte boundary the regions will cover slightly more than
+    /// was asked for. See [`RegionPlanner::over_coverage`].
+    pub fn new(range: Range<u64>, attrs: RegionAttributes) -> Result<RegionPlanner, Error> {
+        if range.start >= range.end || range.end > ADDRESS_SPACE_END {
+            return Err(Error::InvalidRange);
+        }
+        let start = range.start & !(MIN_REGION_SIZE - 1);
+        let end = range.end.next_multiple_of(MIN_REGION_SIZE);
+        Ok(RegionPlanner {
+            attrs,
+            requested: range,
+            covered: start..end,
+            next: start,
+        })
+    }
+
+    /// The range of addresses the regions actually cover
+    pub fn covered(&self) -> Range<u64> {
+        self.covered.clone()
+    }
+
+    /// How many bytes outside the requested range the regions cover
+    ///
+    /// Zero means the range is covered exactly.
+    pub fn over_coverage(&self) -> u64 {
+        (self.requested.start - self.covered.start) + (self.covered.end - self.requested.end)
+    }
+}
+
+impl Iterator for RegionPlanner {
+    type Item = Region;
+
+    fn next(&mut self) -> Option<Region> {
+        if self.next >= self.covered.end {
+            return None;
+        }
+        let step = best_step(self.next, self.covered.end);
+        self.next = step.end;
+        Some(Region {
+            base: step.base as usize as *mut u8,
+            size: RegionSize::new_with_raw_value(u5::new((step.size_log2 - 1) as u8)),
+            subregion_mask: step.subregion_mask,
+            enabled: true,
+            no_exec: self.attrs.no_exec,
+            access: self.attrs.access,
+            mem_attr: self.attrs.mem_attr.clone(),
+        })
+    }
+}
+
+/// One region, and how far through the range it gets us
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    size_log2: u32,
+    base: u64,
+    subregion_mask: u8,
+    end: u64,
+}
+
+/// Find the region starting at `start` which covers the most of `start..end`
+///
+/// Both `start` and `end` must be multiples of [`MIN_REGION_SIZE`]. If several
+/// regions reach equally far, the smallest is used.
+fn best_step(start: u64, end: u64) -> Step {
+    let mut best: Option<Step> = None;
+    for size_log2 in MIN_REGION_SIZE.trailing_zeros()..=32 {
+        let size = 1u64 << size_log2;
+        let base = start & !(size - 1);
+        let limit = end.min(base + size);
+        let step = if size >= MIN_SUBREGION_REGION_SIZE {
+            let subregion_size = size / 8;
+            if start % subregion_size != 0 {
+                continue;
+            }
+            let first = (start - base) / subregion_size;
+            let last = (limit - base) / subregion_size;
+            if last <= first {
+                continue;
+            }
+            let enabled = ((1u16 << last) - (1u16 << first)) as u8;
+            Step {
+                size_log2,
+                base,
+                subregion_mask: !enabled,
+                end: base + last * subregion_size,
+            }
+        } else {
+            if base != start || base + size > end {
+                continue;
+            }
+            Step {
+                size_log2,
+                base,
+                subregion_mask: 0,
+                end: base + size,
+            }
+        };
+        if best.as_ref().is_none_or(|b| step.end > b.end) {
+            best = Some(step);
+        }
+    }
+    // A minimum sized region at `start` always fits, so there is always a step
+    best.expect("no region fits")
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    const ATTRS: RegionAttributes = RegionAttributes {
+        no_exec: false,
+        access: AccessPerms::ReadOnly,
+        mem_attr: MemAttr::WriteBackWriteAllocate { shareable: false },
+    };
+
+    fn summary(planner: RegionPlanner) -> Vec<(usize, RegionSize, u8)> {
+        planner
+            .map(|r| (r.base as usize, r.size, r.subregion_mask))
+            .collect()
+    }
+
+    #[test]
+    fn power_of_two() {
+        let planner = RegionPlanner::new(0x2000_0000..0x2001_0000, ATTRS).unwrap();
+        assert_eq!(planner.over_coverage(), 0);
+        assert_eq!(summary(planner), [(0x2000_0000, RegionSize::_64K, 0x00)]);
+    }
+
+    #[test]
+    fn text_section() {
+        let planner = RegionPlanner::new(0x0000_0000..0x0000_9A40, ATTRS).unwrap();
+        assert_eq!(planner.over_coverage(), 0);
+        let regions: Vec<Region> = planner.clone().collect();
+        assert!(regions.iter().all(|r| r.size.is_aligned(r.base)));
+        assert!(regions.iter().all(|r| r.access == AccessPerms::ReadOnly));
+        assert_eq!(
+            summary(planner),
+            [
+                (0x0000_0000, RegionSize::_32K, 0x00),
+                (0x0000_8000, RegionSize::_8K, 0xC0),
+                (0x0000_9800, RegionSize::_512b, 0x00),
+                (0x0000_9A00, RegionSize::_64B, 0x00),
+            ]
+        );
+    }
+
+    #[test]
+    fn unaligned_range() {
+        let planner = RegionPlanner::new(0x1010..0x1FF0, ATTRS).unwrap();
+        assert_eq!(planner.covered(), 0x1000..0x2000);
+        assert_eq!(planner.over_coverage(), 0x20);
+        assert_eq!(summary(planner), [(0x1000, RegionSize::_4K, 0x00)]);
+        assert_eq!(
+            RegionPlanner::new(0x1000..0x1000, ATTRS).unwrap_err(),
+            Error::InvalidRange
+        );
+    }
+}
@@ -10,6 +10,10 @@ use arbitrary_int::{u26, u3};
 
 use crate::register;
 
+pub mod evaluate;
+pub mod validate;
+pub mod window;
+
 #[doc(inline)]
 pub use register::hprbar::{AccessPerms as El2AccessPerms, Shareability as El2Shareability};
 #[doc(inline)]
@@ -24,6 +28,8 @@ pub enum Error {
     InvalidMair(u8),
     /// Found a region with invalid alignment
     UnalignedRegion(core::ops::RangeInclusive<*mut u8>),
+    /// Found a task window which crosses a multiple of sixteen regions
+    WindowStraddlesBank,
 }
 
 /// Represents our PMSAv8-32 EL1 MPU
@@ -372,6 +378,17 @@ pub struct El1Config<'a> {
     pub memory_attributes: &'a [MemAttr],
 }
 
+impl El1Config<'_> {
+    /// Check this config for overlapping regions and bad memory attributes
+    ///
+    /// See [`validate::validate`] for what is checked.
+    pub fn validate(&self) -> Result<(), validate::ValidationError> {
+        validate::validate_with(self.regions.len(), self.memory_attributes.len(), |i| {
+            (&self.regions[i]).into()
+        })
+    }
+}
+
 /// Configuration for the PMSAv8-32 MPU
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct El1Region {
@@ -417,6 +434,17 @@ pub struct El2Config<'a> {
     pub memory_attributes: &'a [MemAttr],
 }
 
+impl El2Config<'_> {
+    /// Check this config for overlapping regions and bad memory attributes
+    ///
+    /// See [`validate::validate`] for what is checked.
+    pub fn validate(&self) -> Result<(), validate::ValidationError> {
+        validate::validate_with(self.regions.len(), self.memory_attributes.len(), |i| {
+            (&self.regions[i]).into()
+        })
+    }
+}
+
 /// Configuration for the PMSAv8-32 EL2 MPU
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct El2Region {
@@ -540,4 +568,40 @@ mod test {
             mem_attr.to_bits()
         );
     }
+
+    #[test]
+    fn validate_config() {
+        let region = El1Region {
+            range: 0x0001_0000 as *mut u8..=0x0001_FFFF as *mut u8,
+            shareability: El1Shareability::NonShareable,
+            access: El1AccessPerms::ReadWriteNoEL0,
+            no_exec: false,
+            mair: 0,
+            enable: true,
+        };
+        let regions = [
+            region.clone(),
+            El1Region {
+                range: 0x0001_8000 as *mut u8..=0x0002_FFFF as *mut u8,
+                ..region
+            },
+        ];
+        let config = El1Config {
+            background_config: true,
+            regions: &regions,
+            memory_attributes: &[MemAttr::StronglyOrdered],
+        };
+        assert_eq!(
+            config.validate(),
+            Err(validate::ValidationError::Overlap {
+                first: 0,
+                second: 1
+            })
+        );
+        let config = El1Config {
+            regions: &regions[..1],
+            ..config
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
 }
@@ -0,0 +1,249 @@
+//! Evaluates accesses against a PMSAv8-32 MPU configuration
+//!
+//! Regions are matched with the bottom six bits of their start and end
+//! addresses ignored, as the MPU does. An address in more than one enabled
+//! region is a fault.
+
+use crate::evaluate::{Access, Evaluate, Fault, Matched, Outcome, Permissions};
+
+use super::{
+    El1AccessPerms, El1Config, El1Region, El1Shareability, El2AccessPerms, El2Config, El2Region,
+    El2Shareability, MemAttr,
+};
+
+/// The memory attributes of a PMSAv8-32 region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionAttributes<S> {
+    /// The memory attribute the region selects
+    ///
+    /// A selector past the end of the config's memory attributes gives
+    /// [`MemAttr::StronglyOrdered`], as the unused MAIR fields are zero.
+    pub mem_attr: MemAttr,
+    /// Shareability of the region
+    pub shareability: S,
+}
+
+/// Does an inclusive range, as the MPU sees it, contain `address`?
+fn contains(range: &core::ops::RangeInclusive<*mut u8>, address: u32) -> bool {
+    let start = *range.start() as usize as u32 & !0x3F;
+    let end = *range.end() as usize as u32 | 0x3F;
+    start <= address && address <= end
+}
+
+/// Find the only region that `matches`
+fn find<R>(regions: &[R], matches: impl Fn(&R) -> bool) -> Result<Option<(usize, &R)>, Fault> {
+    let mut found: Option<(usize, &R)> = None;
+    for (index, region) in regions.iter().enumerate() {
+        if !matches(region) {
+            continue;
+        }
+        if let Some((first, _)) = found {
+            return Err(Fault::Overlap {
+                first,
+                second: index,
+            });
+        }
+        found = Some((index, region));
+    }
+    Ok(found)
+}
+
+/// Look up a MAIR selector in a config's memory attributes
+fn mem_attr(memory_attributes: &[MemAttr], mair: u8) -> MemAttr {
+    memory_attributes
+        .get(mair as usize)
+        .cloned()
+        .unwrap_or(MemAttr::StronglyOrdered)
+}
+
+/// The result of an access which matched no region
+fn background<A>(
+    address: u32,
+    access: Access,
+    background_config: bool,
+) -> Result<Outcome<Matched<A>>, Fault> {
+    if access.privileged && background_config {
+        Ok(Outcome {
+            physical_address: address as u64,
+            attributes: Matched::Background,
+            permissions: Permissions::ALL,
+        })
+    } else {
+        Err(Fault::Background)
+    }
+}
+
+impl Evaluate for El1Config<'_> {
+    type Attributes = Matched<RegionAttributes<El1Shareability>>;
+
+    /// Work out what happens when `access` is made to `address`
+    ///
+    /// The background region gives privileged accesses full access; the
+    /// execute-never parts of the default memory map are not modelled.
+    fn evaluate(&self, address: u32, access: Access) -> Result<Outcome<Self::Attributes>, Fault> {
+        let found = find(self.regions, |r: &El1Region| {
+            r.enable && contains(&r.range, address)
+        })?;
+        let Some((index, region)) = found else {
+            return background(address, access, self.background_config);
+        };
+        let (read, write) = match (region.access, access.privileged) {
+            (El1AccessPerms::ReadWrite, _) | (El1AccessPerms::ReadWriteNoEL0, true) => (true, true),
+            (El1AccessPerms::ReadOnly, _) | (El1AccessPerms::ReadOnlyNoEL0, true) => (true, false),
+            (El1AccessPerms::ReadWriteNoEL0 | El1AccessPerms::ReadOnlyNoEL0, false) => {
+                (false, false)
+            }
+        };
+        let permissions = Permissions::new(read, write, region.no_exec);
+        if !permissions.allows(access.kind) {
+            return Err(Fault::MpuPermission);
+        }
+        Ok(Outcome {
+            physical_address: address as u64,
+            attributes: Matched::Region {
+                index,
+                attributes: RegionAttributes {
+                    mem_attr: mem_attr(self.memory_attributes, region.mair),
+                    shareability: region.shareability,
+                },
+            },
+            permissions,
+        })
+    }
+}
+
+impl Evaluate for El2Config<'_> {
+    type Attributes = Matched<RegionAttributes<El2Shareability>>;
+
+    /// Work out what happens when `access` is made to `address`
+    ///
+    /// A privileged access is one made at EL2. The background region gives
+    /// those full access; the execute-never parts of the default memory map
+    /// are not modelled.
+    fn evaluate(&self, address: u32, access: Access) -> Result<Outcome<Self::Attributes>, Fault> {
+        let found = find(self.regions, |r: &El2Region| {
+            r.enable && contains(&r.range, address)
+        })?;
+        let Some((index, region)) = found else {
+            return background(address, access, self.background_config);
+        };
+        let (read, write) = match (region.access, access.privileged) {
+            (El2AccessPerms::ReadWrite, _) | (El2AccessPerms::ReadWriteNoEL10, true) => {
+                (true, true)
+            }
+            (El2AccessPerms::ReadOnly, _) | (El2AccessPerms::ReadOnlyNoEL10, true) => (true, false),
+            (El2AccessPerms::ReadWriteNoEL10 | El2AccessPerms::ReadOnlyNoEL10, false) => {
+                (false, false)
+            }
+        };
+        let permissions = Permissions::new(read, write, region.no_exec);
+        if !permissions.allows(access.kind) {
+            return Err(Fault::MpuPermission);
+        }
+        Ok(Outcome {
+            physical_address: address as u64,
+            attributes: Matched::Region {
+                index,
+                attributes: RegionAttributes {
+                    mem_attr: mem_attr(self.memory_attributes, region.mair),
+                    shareability: region.shareability,
+                },
+            },
+            permissions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pmsav8::Cacheable;
+
+    const FLASH: El1Region = El1Region {
+        range: 0x0800_0000 as *mut u8..=0x0803_FFFF as *mut u8,
+        shareability: El1Shareability::NonShareable,
+        access: El1AccessPerms::ReadOnly,
+        no_exec: false,
+        mair: 0,
+        enable: true,
+    };
+
+    const RAM: El1Region = El1Region {
+        range: 0x2000_0000 as *mut u8..=0x2000_FFFF as *mut u8,
+        shareability: El1Shareability::InnerShareable,
+        access: El1AccessPerms::ReadWriteNoEL0,
+        no_exec: true,
+        mair: 1,
+        enable: true,
+    };
+
+    #[test]
+    fn permissions() {
+        let regions = [FLASH, RAM];
+        let config = El1Config {
+            background_config: false,
+            regions: &regions,
+            memory_attributes: &[MemAttr::NormalMemory {
+                outer: Cacheable::NonCacheable,
+                inner: Cacheable::NonCacheable,
+            }],
+        };
+        // MAIR selector 1 is past the end of the attributes, so reads as zero
+        assert_eq!(
+            config.evaluate(0x2000_0000, Access::PRIVILEGED_WRITE),
+            Ok(Outcome {
+                physical_address: 0x2000_0000,
+                attributes: Matched::Region {
+                    index: 1,
+                    attributes: RegionAttributes {
+                        mem_attr: MemAttr::StronglyOrdered,
+                        shareability: El1Shareability::InnerShareable,
+                    },
+                },
+                permissions: Permissions::new(true, true, true),
+            })
+        );
+        assert_eq!(
+            config.evaluate(0x2000_0000, Access::USER_READ),
+            Err(Fault::MpuPermission)
+        );
+        assert_eq!(
+            config.evaluate(0x0800_1000, Access::USER_WRITE),
+            Err(Fault::MpuPermission)
+        );
+        assert!(config.evaluate(0x0800_1000, Access::USER_EXECUTE).is_ok());
+        assert_eq!(
+            config.evaluate(0x4000_0000, Access::PRIVILEGED_READ),
+            Err(Fault::Background)
+        );
+    }
+
+    #[test]
+    fn overlap() {
+        let regions = [
+            FLASH,
+            El1Region {
+                range: 0x0802_0000 as *mut u8..=0x0802_FFFF as *mut u8,
+                ..RAM
+            },
+        ];
+        let config = El1Config {
+            background_config: true,
+            regions: &regions,
+            memory_attributes: &[],
+        };
+        assert_eq!(
+            config.evaluate(0x0802_0000, Access::PRIVILEGED_READ),
+            Err(Fault::Overlap {
+                first: 0,
+                second: 1
+            })
+        );
+        assert_eq!(
+            config
+                .evaluate(0x0804_0000, Access::PRIVILEGED_READ)
+                .map(|o| o.attributes),
+            Ok(Matched::Background)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Checks PMSAv8-32 MPU configurations before they are loaded
+//!
+//! On PMSAv8-32 an access which hits more than one enabled region is a
+//! fault, so unlike PMSAv7 regions cannot be layered on top of each other.
+//! [`validate`] checks a set of regions for that, and for the other mistakes
+//! which the MPU would either reject or silently misinterpret.
+//!
+//! Raw pointers cannot be compared in `const` context, so [`validate`] works
+//! on [`RegionFootprint`]s, which describe a region with plain integers. Use
+//! [`El1Config::validate`](super::El1Config::validate) or
+//! [`El2Config::validate`](super::El2Config::validate) to check a config at
+//! run-time, or describe the same layout with footprints to check it at
+//! compile time:
+//!
+//! ```rust
+//! use aarch32_cpu::pmsav8::validate::{validate, RegionFootprint};
+//!
+//! const REGIONS: [RegionFootprint; 2] = [
+//!     RegionFootprint::new(0x0000_0000..=0x0003_FFFF, 0),
+//!     RegionFootprint::new(0x2000_0000..=0x2000_FFFF, 1),
+//! ];
+//! const _: () = assert!(validate(&REGIONS, 2).is_ok());
+//! ```
+
+use core::ops::RangeInclusive;
+
+use super::{El1Region, El2Region};
+
+/// The number of memory attributes in {MAIR0, MAIR1} or {HMAIR0, HMAIR1}
+const NUM_MEMORY_ATTRIBUTES: usize = 8;
+
+/// Regions must start and end on a 64-byte boundary
+const REGION_ALIGN_MASK: u32 = 0x3F;
+
+/// The parts of an MPU region which decide whether a config is valid
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionFootprint {
+    /// The first address in the region
+    pub start: u32,
+    /// The last address in the region
+    pub end: u32,
+    /// Which Memory Attribute the region selects
+    pub mair: u8,
+    /// Is this region enabled?
+    pub enable: bool,
+}
+
+impl RegionFootprint {
+    /// Describe an enabled region covering `range`
+    pub const fn new(range: RangeInclusive<u32>, mair: u8) -> RegionFootprint {
+        RegionFootprint {
+            start: *range.start(),
+            end: *range.end(),
+            mair,
+            enable: true,
+        }
+    }
+
+    /// Do these two regions share any addresses?
+    pub const fn overlaps(&self, other: &RegionFootprint) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl From<&El1Region> for RegionFootprint {
+    fn from(region: &El1Region) -> RegionFootprint {
+        RegionFootprint {
+            start: *region.range.start() as usize as u32,
+            end: *region.range.end() as usize as u32,
+            mair: region.mair,
+            enable: region.enable,
+        }
+    }
+}
+
+impl From<&El2Region> for RegionFootprint {
+    fn from(region: &El2Region) -> RegionFootprint {
+        RegionFootprint {
+            start: *region.range.start() as usize as u32,
+            end: *region.range.end() as usize as u32,
+            mair: region.mair,
+            enable: region.enable,
+        }
+    }
+}
+
+/// Ways an MPU configuration can be invalid
+///
+/// Regions and memory attributes are identified by their index in the
+/// config.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ValidationError {
+    /// More memory attributes were given than the MPU supports
+    TooManyAttributes(usize),
+    /// A region has a length of zero (its end is one below its start)
+    ZeroLength {
+        /// The region with the bad range
+        region: usize,
+    },
+    /// A region's end is more than one below its start
+    InvertedRange {
+        /// The region with the bad range
+        region: usize,
+    },
+    /// A region does not start and end on a 64-byte boundary
+    UnalignedRegion {
+        /// The region with the bad range
+        region: usize,
+    },
+    /// A region selects a memory attribute which was not given
+    InvalidMair {
+        /// The region with the bad selector
+        region: usize,
+        /// The memory attribute it selected
+        mair: u8,
+    },
+    /// Two enabled regions share some addresses
+    Overlap {
+        /// The region which comes first in the config
+        first: usize,
+        /// The region which comes second in the config
+        second: usize,
+    },
+    /// A memory attribute is not selected by any enabled region
+    UnusedMair {
+        /// The memory attribute which is unused
+        index: usize,
+    },
+}
+
+/// Check a set of regions, which select from `memory_attributes` attributes
+///
+/// Every region is checked for a valid range and memory attribute, even if
+/// it is disabled. Only enabled regions are checked for overlaps, and only
+/// they count as using a memory attribute.
+///
+/// The first problem found is returned.
+pub const fn validate(
+    regions: &[RegionFootprint],
+    memory_attributes: usize,
+) -> Result<(), ValidationError> {
+    if memory_attributes > NUM_MEMORY_ATTRIBUTES {
+        return Err(ValidationError::TooManyAttributes(memory_attributes));
+    }
+    let mut i = 0;
+    while i < regions.len() {
+        if let Err(e) = check_region(i, &regions[i], memory_attributes) {
+            return Err(e);
+        }
+        i += 1;
+    }
+    let mut i = 0;
+    while i < regions.len() {
+        let mut j = i + 1;
+        while j < regions.len() {
+            if let Err(e) = check_pair(i, &regions[i], j, &regions[j]) {
+                return Err(e);
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    let mut used = 0u8;
+    let mut i = 0;
+    while i < regions.len() {
+        if regions[i].enable {
+            used |= 1 << regions[i].mair;
+        }
+        i += 1;
+    }
+    check_used(used, memory_attributes)
+}
+
+/// Like [`validate`], but takes the regions one at a time from `get`
+///
+/// This lets us check the pointer-based regions without copying them into a
+/// buffer first.
+pub(crate) fn validate_with(
+    num_regions: usize,
+    memory_attributes: usize,
+    get: impl Fn(usize) -> RegionFootprint,
+) -> Result<(), ValidationError> {
+    if memory_attributes > NUM_MEMORY_ATTRIBUTES {
+        return Err(ValidationError::TooManyAttributes(memory_attributes));
+    }
+    for i in 0..num_regions {
+        check_region(i, &get(i), memory_attributes)?;
+    }
+    for i in 0..num_regions {
+        let first = get(i);
+        for j in (i + 1)..num_regions {
+            check_pair(i, &first, j, &get(j))?;
+        }
+    }
+    let used = (0..num_regions)
+        .map(&get)
+        .filter(|r| r.enable)
+        .fold(0u8, |used, r| used | 1 << r.mair);
+    check_used(used, memory_attributes)
+}
+
+/// Check the range and memory attribute of a single region
+const fn check_region(
+    idx: usize,
+    region: &RegionFootprint,
+    memory_attributes: usize,
+) -> Result<(), ValidationError> {
+    if region.start != 0 && region.end == region.start - 1 {
+        return Err(ValidationError::ZeroLength { region: idx });
+    }
+    if region.end < region.start {
+        return Err(ValidationError::InvertedRange { region: idx });
+    }
+    if region.start & REGION_ALIGN_MASK != 0 || region.end & REGION_ALIGN_MASK != REGION_ALIGN_MASK
+    {
+        return Err(ValidationError::UnalignedRegion { region: idx });
+    }
+    if region.mair as usize >= memory_attributes {
+        return Err(ValidationError::InvalidMair {
+            region: idx,
+            mair: region.mair,
+        });
+    }
+    Ok(())
+}
+
+/// Check that two regions do not overlap, if they are both enabled
+const fn check_pair(
+    first_idx: usize,
+    first: &RegionFootprint,
+    second_idx: usize,
+    second: &RegionFootprint,
+) -> Result<(), ValidationError> {
+    if first.enable && second.enable && first.overlaps(second) {
+        return Err(ValidationError::Overlap {
+            first: first_idx,
+            second: second_idx,
+        });
+    }
+    Ok(())
+}
+
+/// Check that every memory attribute has a bit set in `used`
+const fn check_used(used: u8, memory_attributes: usize) -> Result<(), ValidationError> {
+    let mut index = 0;
+    while index < memory_attributes {
+        if used & (1 << index) == 0 {
+            return Err(ValidationError::UnusedMair { index });
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REGIONS: [RegionFootprint; 3] = [
+        RegionFootprint::new(0x0000_0000..=0x0003_FFFF, 0),
+        RegionFootprint::new(0x0004_0000..=0x0004_003F, 1),
+        RegionFootprint::new(0x2000_0000..=0x2000_FFFF, 1),
+    ];
+
+    #[test]
+    fn valid() {
+        const RESULT: Result<(), ValidationError> = validate(&REGIONS, 2);
+        assert_eq!(RESULT, Ok(()));
+        assert_eq!(validate(&[], 0), Ok(()));
+        assert_eq!(
+            validate(&[RegionFootprint::new(0..=u32::MAX, 0)], 1),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn bad_ranges() {
+        let mut regions = REGIONS;
+        regions[1].end = regions[1].start - 1;
+        assert_eq!(
+            validate(&regions, 2),
+            Err(ValidationError::ZeroLength { region: 1 })
+        );
+        regions[1].end = regions[1].start - 0x41;
+        assert_eq!(
+            validate(&regions, 2),
+            Err(ValidationError::InvertedRange { region: 1 })
+        );
+        regions[1].end = regions[1].start + 0x1F;
+        assert_eq!(
+            validate(&regions, 2),
+            Err(ValidationError::UnalignedRegion { region: 1 })
+        );
+    }
+
+    #[test]
+    fn bad_mair() {
+        assert_eq!(
+            validate(&REGIONS, 1),
+            Err(ValidationError::InvalidMair { region: 1, mair: 1 })
+        );
+        assert_eq!(
+            validate(&REGIONS, 3),
+            Err(ValidationError::UnusedMair { index: 2 })
+        );
+        assert_eq!(
+            validate(&REGIONS, 9),
+            Err(ValidationError::TooManyAttributes(9))
+        );
+        // A disabled region does not count as using an attribute
+        let mut regions = REGIONS;
+        regions[0].enable = false;
+        assert_eq!(
+            validate(&regions, 2),
+            Err(ValidationError::UnusedMair { index: 0 })
+        );
+    }
+
+    #[test]
+    fn overlap() {
+        let mut regions = REGIONS;
+        regions[2] = RegionFootprint::new(0x0000_0040..=0x0000_007F, 1);
+        assert_eq!(
+            validate(&regions, 2),
+            Err(ValidationError::Overlap {
+                first: 0,
+                second: 2
+            })
+        );
+        regions[0].enable = false;
+        regions[2].mair = 0;
+        assert_eq!(
+            validate(&regions, 2),
+            Ok(()),
+            "disabled regions may overlap"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+//! Swaps a small window of MPU regions on each context switch
+//!
+//! A typical RTOS programs a fixed set of "kernel" regions once, and then
+//! gives each task a few regions of its own which are rewritten whenever that
+//! task is scheduled. Going through PRSELR costs an extra register write for
+//! every region, so this module uses the direct-access registers instead.
+//!
+//! `PRBAR<n>` and `PRLAR<n>` access region `(PRSELR.REGION[7:4] << 4) | n`,
+//! so any sixteen regions which share the top four bits of their number can
+//! be written after a single write to PRSELR. A [`TaskWindow`] is therefore
+//! not allowed to straddle a multiple of sixteen.
+//!
+//! ```rust,no_run
+//! use aarch32_cpu::pmsav8::{window::TaskRegions, El1Mpu};
+//!
+//! # fn example(task_regions: &TaskRegions<2>) -> Result<(), aarch32_cpu::pmsav8::Error> {
+//! let mut mpu = unsafe { El1Mpu::new() };
+//! // Regions 0..=5 are for the kernel, and set up with `El1Mpu::configure`
+//! let window = mpu.task_window::<2>(6)?;
+//! // Then on each context switch
+//! mpu.load_task_regions(&window, task_regions);
+//! # Ok(())
+//! # }
+//! ```
+
+use arbitrary_int::{u26, u3};
+
+use super::{El1Mpu, El1Region, El2Mpu, El2Region, Error};
+use crate::register;
+
+/// The number of regions reachable through the direct-access registers
+const REGIONS_PER_BANK: u8 = 16;
+
+/// An MPU region, already encoded for the base and limit registers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncodedRegion {
+    base: u32,
+    limit: u32,
+}
+
+impl EncodedRegion {
+    /// A region which matches no addresses
+    pub const DISABLED: EncodedRegion = EncodedRegion { base: 0, limit: 0 };
+
+    /// Encode an EL1 MPU region
+    ///
+    /// Fails in the same cases as [`El1Mpu::set_region`].
+    pub fn new(region: &El1Region) -> Result<EncodedRegion, Error> {
+        let (start, end) = check_range(&region.range, region.mair)?;
+        let base = register::Prbar::new_with_raw_value(0)
+            .with_base(u26::from_u32(start >> 6))
+            .with_access_perms(region.access)
+            .with_nx(region.no_exec)
+            .with_shareability(region.shareability);
+        let limit = register::Prlar::new_with_raw_value(0)
+            .with_limit(u26::from_u32(end >> 6))
+            .with_enabled(region.enable)
+            .with_mair(u3::from_u8(region.mair));
+        Ok(EncodedRegion {
+            base: base.raw_value(),
+            limit: limit.raw_value(),
+        })
+    }
+
+    /// Encode an EL2 MPU region
+    ///
+    /// Fails in the same cases as [`El2Mpu::set_region`].
+    pub fn new_el2(region: &El2Region) -> Result<EncodedRegion, Error> {
+        let (start, end) = check_range(&region.range, region.mair)?;
+        let base = register::Hprbar::new_with_raw_value(0)
+            .with_base(u26::from_u32(start >> 6))
+            .with_access_perms(region.access)
+            .with_nx(region.no_exec)
+            .with_shareability(region.shareability);
+        let limit = register::Hprlar::new_with_raw_value(0)
+            .with_limit(u26::from_u32(end >> 6))
+            .with_enabled(region.enable)
+            .with_mair(u3::from_u8(region.mair));
+        Ok(EncodedRegion {
+            base: base.raw_value(),
+            limit: limit.raw_value(),
+        })
+    }
+
+    /// The value for PRBAR or HPRBAR
+    pub const fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The value for PRLAR or HPRLAR
+    pub const fn limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+/// Check a region is 64-byte aligned and uses a valid MAIR selector
+fn check_range(range: &core::ops::RangeInclusive<*mut u8>, mair: u8) -> Result<(u32, u32), Error> {
+    let start = *range.start() as usize as u32;
+    let end = *range.end() as usize as u32;
+    // Check for 64-byte alignment (0x3F is six bits)
+    if start & 0x3F != 0 || end & 0x3F != 0x3F {
+        return Err(Error::UnalignedRegion(range.clone()));
+    }
+    if mair > 7 {
+        return Err(Error::InvalidMair(mair));
+    }
+    Ok((start, end))
+}
+
+/// The regions belonging to one task, ready to be loaded into a [`TaskWindow`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TaskRegions<const N: usize> {
+    regions: [EncodedRegion; N],
+}
+
+impl<const N: usize> TaskRegions<N> {
+    /// A task with every region in the window disabled
+    pub const EMPTY: TaskRegions<N> = TaskRegions {
+        regions: [EncodedRegion::DISABLED; N],
+    };
+
+    /// Encode the regions for an EL1 task
+    pub fn new(regions: &[El1Region; N]) -> Result<TaskRegions<N>, Error> {
+        let mut result = Self::EMPTY;
+        for (encoded, region) in result.regions.iter_mut().zip(regions) {
+            *encoded = EncodedRegion::new(region)?;
+        }
+        Ok(result)
+    }
+
+    /// Encode the regions for an EL2 task
+    pub fn new_el2(regions: &[El2Region; N]) -> Result<TaskRegions<N>, Error> {
+        let mut result = Self::EMPTY;
+        for (encoded, region) in result.regions.iter_mut().zip(regions) {
+            *encoded = EncodedRegion::new_el2(region)?;
+        }
+        Ok(result)
+    }
+
+    /// Get the encoded regions
+    pub const fn regions(&self) -> &[EncodedRegion; N] {
+        &self.regions
+    }
+}
+
+/// A run of `N` MPU regions which is rewritten on each context switch
+///
+/// Get one from [`El1Mpu::task_window`] or [`El2Mpu::task_window`], and only
+/// use it with the MPU it came from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TaskWindow<const N: usize> {
+    first: u8,
+}
+
+impl<const N: usize> TaskWindow<N> {
+    /// Check that a window of `N` regions starting at `first` fits in an
+    /// MPU with `num_regions` regions, and does not straddle a bank of sixteen
+    pub const fn new(first: u8, num_regions: u8) -> Result<TaskWindow<N>, Error> {
+        let end = first as usize + N;
+        if N == 0 || end > num_regions as usize {
+            return Err(Error::TooManyRegions);
+        }
+        if (first % REGIONS_PER_BANK) as usize + N > REGIONS_PER_BANK as usize {
+            return Err(Error::WindowStraddlesBank);
+        }
+        Ok(TaskWindow { first })
+    }
+
+    /// The first region in the window
+    pub const fn first(&self) -> u8 {
+        self.first
+    }
+
+    /// The value to write to PRSELR or HPRSELR to reach this window
+    const fn bank(&self) -> u32 {
+        (self.first & !(REGIONS_PER_BANK - 1)) as u32
+    }
+
+    /// The number of the first region within its bank
+    const fn offset(&self) -> u8 {
+        self.first % REGIONS_PER_BANK
+    }
+}
+
+/// Generates a function which writes a region with the direct-access registers
+macro_rules! direct_writer {
+    ($name:ident, $($n:literal => $bar:ident, $lar:ident;)*) => {
+        /// Write region `n` of the current bank
+        fn $name(n: u8, region: EncodedRegion) {
+            // Safety: the values came from an `EncodedRegion`, so the
+            // reserved bits are clear
+            unsafe {
+                match n {
+                    $($n => {
+                        register::$bar::write(register::$bar(region.base));
+                        register::$lar::write(register::$lar(region.limit));
+                    })*
+                    _ => unreachable!(),
+                }
+            }
+        }
+    };
+}
+
+direct_writer!(write_el1,
+    0 => Prbar0, Prlar0; 1 => Prbar1, Prlar1; 2 => Prbar2, Prlar2; 3 => Prbar3, Prlar3;
+    4 => Prbar4, Prlar4; 5 => Prbar5, Prlar5; 6 => Prbar6, Prlar6; 7 => Prbar7, Prlar7;
+    8 => Prbar8, Prlar8; 9 => Prbar9, Prlar9; 10 => Prbar10, Prlar10; 11 => Prbar11, Prlar11;
+    12 => Prbar12, Prlar12; 13 => Prbar13, Prlar13; 14 => Prbar14, Prlar14; 15 => Prbar15, Prlar15;
+);
+
+direct_writer!(write_el2,
+    0 => Hprbar0, Hprlar0; 1 => Hprbar1, Hprlar1; 2 => Hprbar2, Hprlar2; 3 => Hprbar3, Hprlar3;
+    4 => Hprbar4, Hprlar4; 5 => Hprbar5, Hprlar5; 6 => Hprbar6, Hprlar6; 7 => Hprbar7, Hprlar7;
+    8 => Hprbar8, Hprlar8; 9 => Hprbar9, Hprlar9; 10 => Hprbar10, Hprlar10; 11 => Hprbar11, Hprlar11;
+    12 => Hprbar12, Hprlar12; 13 => Hprbar13, Hprlar13; 14 => Hprbar14, Hprlar14; 15 => Hprbar15, Hprlar15;
+);
+
+/// Make the new regions visible to the following instructions
+fn sync() {
+    #[cfg(arm_architecture = "v8-r")]
+    {
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+}
+
+impl El1Mpu {
+    /// Reserve regions `first..first + N` for per-task regions
+    ///
+    /// ## Errors
+    ///
+    /// Returns:
+    /// - [Error::TooManyRegions] if the window is empty or runs past the last region.
+    /// - [Error::WindowStraddlesBank] if the window crosses a multiple of sixteen.
+    pub fn task_window<const N: usize>(&self, first: u8) -> Result<TaskWindow<N>, Error> {
+        TaskWindow::new(first, self.num_regions())
+    }
+
+    /// Replace the regions in a task window
+    ///
+    /// This does one write to PRSELR, two writes per region, and then a DSB
+    /// and ISB. The regions are written in order, so run this where nothing
+    /// touches the memory they cover (e.g. in the context switch handler).
+    pub fn load_task_regions<const N: usize>(
+        &mut self,
+        window: &TaskWindow<N>,
+        regions: &TaskRegions<N>,
+    ) {
+        register::Prselr::write(register::Prselr(window.bank()));
+        for (n, region) in regions.regions.iter().enumerate() {
+            write_el1(window.offset() + n as u8, *region);
+        }
+        sync();
+    }
+}
+
+impl El2Mpu {
+    /// Reserve regions `first..first + N` for per-task regions
+    ///
+    /// ## Errors
+    ///
+    /// Returns:
+    /// - [Error::TooManyRegions] if the window is empty or runs past the last region.
+    /// - [Error::WindowStraddlesBank] if the window crosses a multiple of sixteen.
+    pub fn task_window<const N: usize>(&self, first: u8) -> Result<TaskWindow<N>, Error> {
+        TaskWindow::new(first, self.num_regions())
+    }
+
+    /// Replace the regions in a task window
+    ///
+    /// See [`El1Mpu::load_task_regions`].
+    pub fn load_task_regions<const N: usize>(
+        &mut self,
+        window: &TaskWindow<N>,
+        regions: &TaskRegions<N>,
+    ) {
+        register::Hprselr::write(register::Hprselr(window.bank()));
+        for (n, region) in regions.regions.iter().enumerate() {
+            write_el2(window.offset() + n as u8, *region);
+        }
+        sync();
+    }
+
+    /// Which of regions 0 to 31 are enabled?
+    ///
+    /// Bit `n` is set if region `n` is enabled.
+    pub fn enabled_regions(&self) -> u32 {
+        register::Hprenr::read().0
+    }
+
+    /// Enable exactly the regions whose bits are set in `mask`
+    ///
+    /// This sets the enable bit of every HPRLAR in one write to HPRENR.
+    pub fn set_enabled_regions(&mut self, mask: u32) {
+        // Safety: bits for regions that are not implemented are ignored
+        unsafe {
+            register::Hprenr::write(register::Hprenr(mask));
+        }
+        sync();
+    }
+
+    /// Enable the regions whose bits are set in `mask`, leaving the rest alone
+    pub fn enable_regions(&mut self, mask: u32) {
+        self.set_enabled_regions(self.enabled_regions() | mask);
+    }
+
+    /// Disable the regions whose bits are set in `mask`, leaving the rest alone
+    pub fn disable_regions(&mut self, mask: u32) {
+        self.set_enabled_regions(self.enabled_regions() & !mask);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pmsav8::{El1AccessPerms, El1Shareability};
+
+    #[test]
+    fn window_bounds() {
+        assert_eq!(TaskWindow::<4>::new(12, 24).map(|w| w.bank()), Ok(0));
+        assert_eq!(
+            TaskWindow::<4>::new(13, 24),
+            Err(Error::WindowStraddlesBank)
+        );
+        let window = TaskWindow::<4>::new(16, 24).unwrap();
+        assert_eq!((window.bank(), window.offset()), (16, 0));
+        assert_eq!(TaskWindow::<4>::new(21, 24), Err(Error::TooManyRegions));
+        assert_eq!(TaskWindow::<0>::new(0, 24), Err(Error::TooManyRegions));
+    }
+
+    #[test]
+    fn encode() {
+        let region = El1Region {
+            range: 0x2000_0000 as *mut u8..=0x2000_FFFF as *mut u8,
+            shareability: El1Shareability::InnerShareable,
+            access: El1AccessPerms::ReadWrite,
+            no_exec: true,
+            mair: 2,
+            enable: true,
+        };
+        let regions = TaskRegions::new(core::array::from_ref(&region)).unwrap();
+        let encoded = regions.regions()[0];
+        assert_eq!(
+            (encoded.base(), encoded.limit()),
+            (0x2000_001B, 0x2000_FFC5)
+        );
+        let bad = El1Region { mair: 8, ..region };
+        assert_eq!(TaskRegions::new(&[bad]), Err(Error::InvalidMair(8)));
+    }
+}
@@ -0,0 +1,491 @@
+//! Support for the Performance Monitors Extension (PMU)
+//!
+//! See Chapter C12: The Performance Monitors Extension in [Arm Architecture
+//! Reference Manual ARMv7-A and ARMv7-R edition][armv7], and Chapter C12 in
+//! [ARM Architecture Reference Manual Supplement - ARMv8, for the ARMv8-R
+//! AArch32 architecture profile][armv8r].
+//!
+//! ```rust,ignore
+//! let mut pmu = unsafe { aarch32_cpu::pmu::Pmu::new() };
+//! pmu.enable();
+//! let refills = pmu.allocate(Event::L1DataCacheRefill).unwrap();
+//! let (_, snapshot) = pmu.measure(|| hot_loop());
+//! println!("{} cycles, {} refills", snapshot.cycles, snapshot.event(&refills));
+//! ```
+//!
+//! [armv7]: https://developer.arm.com/documentation/ddi0406/latest
+//! [armv8r]: https://developer.arm.com/documentation/ddi0568/latest/
+
+use crate::register;
+
+/// The most event counters the architecture allows (PMCR.N is at most 31)
+pub const MAX_EVENT_COUNTERS: usize = 31;
+
+/// The value written to PMSELR to select the cycle counter
+const CYCLE_COUNTER_SELECT: u32 = 31;
+
+/// Ways this API can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// All of the event counters reported by PMCR.N are in use
+    NoFreeCounters,
+}
+
+/// Architectural and common micro-architectural events
+///
+/// Not every event is implemented on every core - see
+/// [`Pmu::event_supported`]. Use [`Event::Other`] for IMPLEMENTATION DEFINED
+/// events listed in your processor's Technical Reference Manual.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// Software increment (writes to PMSWINC)
+    SoftwareIncrement,
+    /// Level 1 instruction cache refill
+    L1InstructionCacheRefill,
+    /// Level 1 instruction TLB refill
+    L1InstructionTlbRefill,
+    /// Level 1 data cache refill
+    L1DataCacheRefill,
+    /// Level 1 data cache access
+    L1DataCacheAccess,
+    /// Level 1 data TLB refill
+    L1DataTlbRefill,
+    /// Load instruction architecturally executed
+    LoadRetired,
+    /// Store instruction architecturally executed
+    StoreRetired,
+    /// Instruction architecturally executed
+    InstructionsRetired,
+    /// Exception taken
+    ExceptionTaken,
+    /// Exception return architecturally executed
+    ExceptionReturn,
+    /// Write to CONTEXTIDR architecturally executed
+    ContextIdWriteRetired,
+    /// Software change of the PC architecturally executed
+    PcWriteRetired,
+    /// Immediate branch architecturally executed
+    BranchImmediateRetired,
+    /// Procedure return architecturally executed
+    BranchReturnRetired,
+    /// Unaligned load or store architecturally executed
+    UnalignedLoadStoreRetired,
+    /// Mispredicted or not predicted branch speculatively executed
+    BranchMispredicted,
+    /// Cycle
+    CpuCycles,
+    /// Predictable branch speculatively executed
+    BranchPredicted,
+    /// Data memory access
+    MemoryAccess,
+    /// Level 1 instruction cache access
+    L1InstructionCacheAccess,
+    /// Level 1 data cache write-back
+    L1DataCacheWriteBack,
+    /// Level 2 data cache access
+    L2DataCacheAccess,
+    /// Level 2 data cache refill
+    L2DataCacheRefill,
+    /// Level 2 data cache write-back
+    L2DataCacheWriteBack,
+    /// Bus access
+    BusAccess,
+    /// Local memory error
+    MemoryError,
+    /// Instruction speculatively executed
+    InstructionsSpeculated,
+    /// Write to TTBR architecturally executed
+    TtbrWriteRetired,
+    /// Bus cycle
+    BusCycles,
+    /// Odd-numbered counter counts overflows of the preceding even-numbered counter
+    Chain,
+    /// No operation issued because of the frontend
+    StallFrontend,
+    /// No operation issued because of the backend
+    StallBackend,
+    /// Any other event number, as listed in a Technical Reference Manual
+    Other(u16),
+}
+
+impl Event {
+    /// The event number that is written to `PMEVTYPER<n>.evtCount`
+    pub const fn number(&self) -> u16 {
+        match self {
+            Event::SoftwareIncrement => 0x00,
+            Event::L1InstructionCacheRefill => 0x01,
+            Event::L1InstructionTlbRefill => 0x02,
+            Event::L1DataCacheRefill => 0x03,
+            Event::L1DataCacheAccess => 0x04,
+            Event::L1DataTlbRefill => 0x05,
+            Event::LoadRetired => 0x06,
+            Event::StoreRetired => 0x07,
+            Event::InstructionsRetired => 0x08,
+            Event::ExceptionTaken => 0x09,
+            Event::ExceptionReturn => 0x0A,
+            Event::ContextIdWriteRetired => 0x0B,
+            Event::PcWriteRetired => 0x0C,
+            Event::BranchImmediateRetired => 0x0D,
+            Event::BranchReturnRetired => 0x0E,
+            Event::UnalignedLoadStoreRetired => 0x0F,
+            Event::BranchMispredicted => 0x10,
+            Event::CpuCycles => 0x11,
+            Event::BranchPredicted => 0x12,
+            Event::MemoryAccess => 0x13,
+            Event::L1InstructionCacheAccess => 0x14,
+            Event::L1DataCacheWriteBack => 0x15,
+            Event::L2DataCacheAccess => 0x16,
+            Event::L2DataCacheRefill => 0x17,
+            Event::L2DataCacheWriteBack => 0x18,
+            Event::BusAccess => 0x19,
+            Event::MemoryError => 0x1A,
+            Event::InstructionsSpeculated => 0x1B,
+            Event::TtbrWriteRetired => 0x1C,
+            Event::BusCycles => 0x1D,
+            Event::Chain => 0x1E,
+            Event::StallFrontend => 0x23,
+            Event::StallBackend => 0x24,
+            Event::Other(n) => *n,
+        }
+    }
+}
+
+/// An event counter that has been allocated from the [`Pmu`]
+///
+/// Hand it back with [`Pmu::free`] when you are done with it.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Counter {
+    idx: u8,
+    event: Event,
+}
+
+impl Counter {
+    /// Which hardware event counter is this?
+    pub fn index(&self) -> u8 {
+        self.idx
+    }
+
+    /// Which event is this counter counting?
+    pub fn event(&self) -> Event {
+        self.event
+    }
+}
+
+/// The value of the cycle counter and every allocated event counter
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Snapshot {
+    /// The cycle counter (PMCCNTR)
+    pub cycles: u32,
+    /// The event counters, indexed by [`Counter::index`]
+    ///
+    /// Counters that were not allocated read as zero.
+    pub events: [u32; MAX_EVENT_COUNTERS],
+}
+
+impl Snapshot {
+    /// Get the value for a particular event counter
+    pub fn event(&self, counter: &Counter) -> u32 {
+        self.events[counter.idx as usize]
+    }
+
+    /// Work out how much each counter has advanced since `earlier`
+    ///
+    /// Each counter is assumed to have wrapped at most once.
+    pub fn since(&self, earlier: &Snapshot) -> Snapshot {
+        let mut events = [0; MAX_EVENT_COUNTERS];
+        for (idx, count) in events.iter_mut().enumerate() {
+            *count = self.events[idx].wrapping_sub(earlier.events[idx]);
+        }
+        Snapshot {
+            cycles: self.cycles.wrapping_sub(earlier.cycles),
+            events,
+        }
+    }
+}
+
+/// Represents the Performance Monitors Extension
+pub struct Pmu {
+    allocated: u32,
+}
+
+impl Pmu {
+    /// Create a PMU handle
+    ///
+    /// # Safety
+    ///
+    /// Only create one of these at any given time, as they access shared
+    /// mutable state within the processor and do read-modify-writes on that state.
+    pub unsafe fn new() -> Pmu {
+        Pmu { allocated: 0 }
+    }
+
+    /// How many event counters are there?
+    ///
+    /// This does not include the cycle counter.
+    pub fn num_counters(&self) -> u8 {
+        register::Pmcr::read().n().value()
+    }
+
+    /// Does this core implement the given event?
+    ///
+    /// Checks PMCEID0 and PMCEID1, which only describe the common events
+    /// `0x00..=0x3F`. Any other event number is assumed to be implemented.
+    pub fn event_supported(&self, event: Event) -> bool {
+        match event.number() {
+            n @ 0x00..=0x1F => register::Pmceid0::read().0 & (1 << n) != 0,
+            n @ 0x20..=0x3F => register::Pmceid1::read().0 & (1 << (n - 0x20)) != 0,
+            _ => true,
+        }
+    }
+
+    /// Reset and enable the cycle counter and all event counters
+    ///
+    /// Only counters that have been allocated will actually count.
+    pub fn enable(&mut self) {
+        unsafe {
+            register::Pmcr::modify(|r| {
+                r.set_p(true);
+                r.set_c(true);
+                r.set_d(false);
+                r.set_e(true);
+            });
+            register::Pmcntenset::write(register::Pmcntenset::new_with_raw_value(0).with_c(true));
+        }
+    }
+
+    /// Stop the cycle counter and all of the event counters
+    pub fn disable(&mut self) {
+        unsafe {
+            register::Pmcr::modify(|r| {
+                r.set_e(false);
+            });
+        }
+    }
+
+    /// Reset the cycle counter and all of the event counters to zero
+    pub fn reset(&mut self) {
+        unsafe {
+            register::Pmcr::modify(|r| {
+                r.set_p(true);
+                r.set_c(true);
+            });
+        }
+    }
+
+    /// Find a free event counter, and configure it to count `event`
+    ///
+    /// The counter is reset to zero and enabled.
+    pub fn allocate(&mut self, event: Event) -> Result<Counter, Error> {
+        let idx = (0..self.num_counters())
+            .find(|&idx| self.allocated & (1 << idx) == 0)
+            .ok_or(Error::NoFreeCounters)?;
+        self.allocated |= 1 << idx;
+        write_event_type(
+            idx,
+            register::Pmxevtyper::new_with_raw_value(0).with_evt_count(event.number()),
+        );
+        write_event_counter(idx, 0);
+        unsafe {
+            register::Pmcntenset::write(register::Pmcntenset::new_with_raw_value(1 << idx));
+        }
+        Ok(Counter { idx, event })
+    }
+
+    /// Stop an event counter and make it available for re-use
+    pub fn free(&mut self, counter: Counter) {
+        unsafe {
+            register::Pmcntenclr::write(register::Pmcntenclr(1 << counter.idx));
+            register::Pmintenclr::write(register::Pmintenclr(1 << counter.idx));
+        }
+        self.allocated &= !(1 << counter.idx);
+    }
+
+    /// Read the current value of an event counter
+    pub fn read(&self, counter: &Counter) -> u32 {
+        read_event_counter(counter.idx)
+    }
+
+    /// Read the current value of the cycle counter
+    pub fn cycles(&self) -> u32 {
+        register::Pmccntr::read().0
+    }
+
+    /// Read the cycle counter and every allocated event counter
+    pub fn snapshot(&self) -> Snapshot {
+        let mut events = [0; MAX_EVENT_COUNTERS];
+        for (idx, count) in events.iter_mut().enumerate() {
+            if self.allocated & (1 << idx) != 0 {
+                *count = read_event_counter(idx as u8);
+            }
+        }
+        Snapshot {
+            cycles: self.cycles(),
+            events,
+        }
+    }
+
+    /// Run a closure, and report how many cycles and events it took
+    ///
+    /// The counters must have been enabled with [`Pmu::enable`]. Note that
+    /// the snapshot includes the cost of reading the counters themselves.
+    pub fn measure<F, R>(&mut self, f: F) -> (R, Snapshot)
+    where
+        F: FnOnce() -> R,
+    {
+        let start = self.snapshot();
+        let result = f();
+        let end = self.snapshot();
+        (result, end.since(&start))
+    }
+
+    /// Generate an overflow interrupt when this event counter wraps
+    pub fn overflow_interrupt(&mut self, counter: &Counter, enable: bool) {
+        let mask = 1 << counter.idx;
+        unsafe {
+            if enable {
+                register::Pmintenset::write(register::Pmintenset(mask));
+            } else {
+                register::Pmintenclr::write(register::Pmintenclr(mask));
+            }
+        }
+    }
+
+    /// Generate an overflow interrupt when the cycle counter wraps
+    pub fn cycle_overflow_interrupt(&mut self, enable: bool) {
+        let mask = 1 << CYCLE_COUNTER_SELECT;
+        unsafe {
+            if enable {
+                register::Pmintenset::write(register::Pmintenset(mask));
+            } else {
+                register::Pmintenclr::write(register::Pmintenclr(mask));
+            }
+        }
+    }
+
+    /// Which counters have overflowed?
+    pub fn overflow_status(&self) -> register::Pmovsr {
+        register::Pmovsr::read()
+    }
+
+    /// Clear the given overflow flags
+    ///
+    /// Pass in the value returned from [`Pmu::overflow_status`] to clear
+    /// every flag that was set.
+    pub fn clear_overflow(&mut self, flags: register::Pmovsr) {
+        unsafe {
+            register::Pmovsr::write(flags);
+        }
+    }
+
+    /// Set the cycle counter filter
+    ///
+    /// PMCCFILTR is part of PMUv3, so this is only available on Armv8-R. The
+    /// Armv7 PMU (PMUv2) has no cycle counter filter.
+    #[cfg(any(doc, arm_architecture = "v8-r"))]
+    pub fn set_cycle_filter(&mut self, filter: register::Pmccfiltr) {
+        unsafe {
+            register::Pmccfiltr::write(filter);
+        }
+    }
+}
+
+/// Select an event counter for access through PMXEVTYPER and PMXEVCNTR
+fn select(idx: u8) {
+    unsafe {
+        register::Pmselr::write(register::Pmselr(idx as u32));
+    }
+    // The selection must take effect before we use PMXEVxxx
+    #[cfg(target_arch = "arm")]
+    crate::asm::isb();
+}
+
+/// Set the event type for an event counter
+///
+/// On Armv8-R the first four counters have their own registers, which saves
+/// us going via PMSELR.
+fn write_event_type(idx: u8, value: register::Pmxevtyper) {
+    #[cfg(arm_architecture = "v8-r")]
+    unsafe {
+        let raw = value.raw_value();
+        match idx {
+            0 => return register::Pmevtyper0::write(register::Pmevtyper0::new_with_raw_value(raw)),
+            1 => return register::Pmevtyper1::write(register::Pmevtyper1::new_with_raw_value(raw)),
+            2 => return register::Pmevtyper2::write(register::Pmevtyper2::new_with_raw_value(raw)),
+            3 => return register::Pmevtyper3::write(register::Pmevtyper3::new_with_raw_value(raw)),
+            _ => {}
+        }
+    }
+    select(idx);
+    unsafe {
+        register::Pmxevtyper::write(value);
+    }
+}
+
+/// Set the value of an event counter
+fn write_event_counter(idx: u8, value: u32) {
+    #[cfg(arm_architecture = "v8-r")]
+    unsafe {
+        match idx {
+            0 => return register::Pmevcntr0::write(register::Pmevcntr0(value)),
+            1 => return register::Pmevcntr1::write(register::Pmevcntr1(value)),
+            2 => return register::Pmevcntr2::write(register::Pmevcntr2(value)),
+            3 => return register::Pmevcntr3::write(register::Pmevcntr3(value)),
+            _ => {}
+        }
+    }
+    select(idx);
+    unsafe {
+        register::Pmxevcntr::write(register::Pmxevcntr(value));
+    }
+}
+
+/// Read the value of an event counter
+fn read_event_counter(idx: u8) -> u32 {
+    #[cfg(arm_architecture = "v8-r")]
+    match idx {
+        0 => return register::Pmevcntr0::read().0,
+        1 => return register::Pmevcntr1::read().0,
+        2 => return register::Pmevcntr2::read().0,
+        3 => return register::Pmevcntr3::read().0,
+        _ => {}
+    }
+    select(idx);
+    register::Pmxevcntr::read().0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn event_numbers() {
+        assert_eq!(Event::CpuCycles.number(), 0x11);
+        assert_eq!(Event::InstructionsRetired.number(), 0x08);
+        assert_eq!(Event::L1DataCacheRefill.number(), 0x03);
+        assert_eq!(Event::BranchMispredicted.number(), 0x10);
+        assert_eq!(Event::Other(0xC0).number(), 0xC0);
+    }
+
+    #[test]
+    fn snapshot_wraps() {
+        let mut earlier = Snapshot {
+            cycles: 0xFFFF_FFF0,
+            events: [0; MAX_EVENT_COUNTERS],
+        };
+        earlier.events[3] = 0xFFFF_FFFF;
+        let mut later = Snapshot {
+            cycles: 0x10,
+            events: [0; MAX_EVENT_COUNTERS],
+        };
+        later.events[3] = 4;
+        let delta = later.since(&earlier);
+        assert_eq!(delta.cycles, 0x20);
+        assert_eq!(delta.events[3], 5);
+        assert_eq!(delta.events[0], 0);
+    }
+}
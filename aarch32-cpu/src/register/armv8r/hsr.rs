@@ -1,12 +1,388 @@
 //! Code for managing HSR (*Hyp Syndrome Register*)
 
+use arbitrary_int::{prelude::*, u2, u25, u3, u4, u6};
+
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// HSR (*Hyp Syndrome Register*)
-#[derive(Debug, Copy, Clone)]
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsr {
+    /// Exception Class
+    ///
+    /// Indicates the reason for the exception that this register holds
+    /// information about. Reserved values read as `Err`.
+    #[bits(26..=31, rw)]
+    ec: Option<ExceptionClass>,
+    /// Instruction length bit
+    ///
+    /// `true` for a 32-bit trapped instruction, `false` for a 16-bit one.
+    #[bit(25, rw)]
+    il: bool,
+    /// Instruction Specific Syndrome
+    ///
+    /// Use [`Hsr::decode_iss`] to interpret this field.
+    #[bits(0..=24, rw)]
+    iss: u25,
+}
+
+/// The reason an exception was taken to Hyp mode
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[bitbybit::bitenum(u6, exhaustive = false)]
+pub enum ExceptionClass {
+    /// Unknown reason
+    Unknown = 0x00,
+    /// Trapped WFI or WFE instruction
+    TrappedWfx = 0x01,
+    /// Trapped MCR or MRC access to CP15
+    TrappedCp15McrMrc = 0x03,
+    /// Trapped MCRR or MRRC access to CP15
+    TrappedCp15McrrMrrc = 0x04,
+    /// Trapped MCR or MRC access to CP14
+    TrappedCp14McrMrc = 0x05,
+    /// Trapped LDC or STC access to CP14
+    TrappedCp14LdcStc = 0x06,
+    /// Access to Advanced SIMD or floating-point functionality trapped by HCPTR
+    TrappedFpu = 0x07,
+    /// Trapped VMRS access to a floating-point ID register
+    TrappedVmrs = 0x08,
+    /// Trapped MRRC access to CP14
+    TrappedCp14Mrrc = 0x0C,
+    /// Illegal exception return
+    IllegalExecutionState = 0x0E,
+    /// SVC instruction routed to Hyp mode
+    Svc = 0x11,
+    /// HVC instruction
+    Hvc = 0x12,
+    /// SMC instruction trapped to Hyp mode
+    Smc = 0x13,
+    /// Prefetch Abort from a lower exception level
+    PrefetchAbortFromLower = 0x20,
+    /// Prefetch Abort taken without a change of exception level
+    PrefetchAbortFromCurrent = 0x21,
+    /// PC alignment fault
+    PcAlignment = 0x22,
+    /// Data Abort from a lower exception level
+    DataAbortFromLower = 0x24,
+    /// Data Abort taken without a change of exception level
+    DataAbortFromCurrent = 0x25,
+}
+
+/// The Instruction Specific Syndrome from HSR, decoded according to the
+/// Exception Class
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Iss {
+    /// Syndrome for a trapped WFI or WFE
+    Wfx(WfxIss),
+    /// Syndrome for a trapped MCR or MRC to CP14 or CP15
+    Cp15Trap(Cp15TrapIss),
+    /// Syndrome for a trapped MCRR or MRRC to CP14 or CP15
+    Cp15RrTrap(Cp15RrTrapIss),
+    /// Syndrome for an HVC
+    Hvc(HvcIss),
+    /// Syndrome for an SVC routed to Hyp mode
+    Svc(SvcIss),
+    /// Syndrome for a Prefetch Abort
+    PrefetchAbort(PrefetchAbortIss),
+    /// Syndrome for a Data Abort
+    DataAbort(DataAbortIss),
+    /// An Exception Class with no decoder - this is the raw ISS field
+    Other(u25),
+}
+
+impl Hsr {
+    /// Interpret the ISS field according to the Exception Class
+    pub fn decode_iss(&self) -> Iss {
+        let iss = self.iss();
+        let raw = iss.value();
+        match self.ec() {
+            Ok(ExceptionClass::TrappedWfx) => Iss::Wfx(WfxIss::new_with_raw_value(raw)),
+            Ok(ExceptionClass::TrappedCp15McrMrc | ExceptionClass::TrappedCp14McrMrc) => {
+                Iss::Cp15Trap(Cp15TrapIss::new_with_raw_value(raw))
+            }
+            Ok(ExceptionClass::TrappedCp15McrrMrrc | ExceptionClass::TrappedCp14Mrrc) => {
+                Iss::Cp15RrTrap(Cp15RrTrapIss::new_with_raw_value(raw))
+            }
+            Ok(ExceptionClass::Hvc) => Iss::Hvc(HvcIss::new_with_raw_value(raw)),
+            Ok(ExceptionClass::Svc) => Iss::Svc(SvcIss::new_with_raw_value(raw)),
+            Ok(
+                ExceptionClass::PrefetchAbortFromLower | ExceptionClass::PrefetchAbortFromCurrent,
+            ) => Iss::PrefetchAbort(PrefetchAbortIss::new_with_raw_value(raw)),
+            Ok(ExceptionClass::DataAbortFromLower | ExceptionClass::DataAbortFromCurrent) => {
+                Iss::DataAbort(DataAbortIss::new_with_raw_value(raw))
+            }
+            _ => Iss::Other(iss),
+        }
+    }
+}
+
+/// ISS for a trapped WFI or WFE instruction
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+pub struct WfxIss {
+    /// Is the `cond` field valid?
+    #[bit(24, r)]
+    cv: bool,
+    /// The condition code of the trapped instruction
+    #[bits(20..=23, r)]
+    cond: u4,
+    /// `true` for WFE, `false` for WFI
+    #[bit(0, r)]
+    wfe: bool,
+}
+
+/// ISS for a trapped MCR or MRC instruction
+///
+/// Used for accesses to both CP15 and CP14.
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+pub struct Cp15TrapIss {
+    /// Is the `cond` field valid?
+    #[bit(24, r)]
+    cv: bool,
+    /// The condition code of the trapped instruction
+    #[bits(20..=23, r)]
+    cond: u4,
+    /// The Opc2 value from the instruction
+    #[bits(17..=19, r)]
+    opc2: u3,
+    /// The Opc1 value from the instruction
+    #[bits(14..=16, r)]
+    opc1: u3,
+    /// The CRn value from the instruction
+    #[bits(10..=13, r)]
+    crn: u4,
+    /// The general-purpose register used for the transfer
+    #[bits(5..=8, r)]
+    rt: u4,
+    /// The CRm value from the instruction
+    #[bits(1..=4, r)]
+    crm: u4,
+    /// `true` for a read (MRC), `false` for a write (MCR)
+    #[bit(0, r)]
+    is_read: bool,
+}
+
+/// ISS for a trapped MCRR or MRRC instruction
+///
+/// Used for accesses to both CP15 and CP14.
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+pub struct Cp15RrTrapIss {
+    /// Is the `cond` field valid?
+    #[bit(24, r)]
+    cv: bool,
+    /// The condition code of the trapped instruction
+    #[bits(20..=23, r)]
+    cond: u4,
+    /// The Opc1 value from the instruction
+    #[bits(16..=19, r)]
+    opc1: u4,
+    /// The second general-purpose register used for the transfer
+    #[bits(10..=13, r)]
+    rt2: u4,
+    /// The first general-purpose register used for the transfer
+    #[bits(5..=8, r)]
+    rt: u4,
+    /// The CRm value from the instruction
+    #[bits(1..=4, r)]
+    crm: u4,
+    /// `true` for a read (MRRC), `false` for a write (MCRR)
+    #[bit(0, r)]
+    is_read: bool,
+}
+
+/// ISS for an HVC instruction
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+pub struct HvcIss {
+    /// The immediate value from the instruction
+    #[bits(0..=15, r)]
+    imm16: u16,
+}
+
+/// ISS for an SVC instruction routed to Hyp mode
+#[bitbybit::bitfield(u32, debug, defmt_fields(feature = "defmt"))]
+pub struct SvcIss {
+    /// The immediate value from the instruction
+    ///
+    /// This is the bottom 16 bits of the A32 `imm24`, or the T32 `imm8`. It
+    /// is UNKNOWN if the instruction was conditional.
+    #[bits(0..=15, r)]
+    imm16: u16,
+}
+
+/// ISS for a Prefetch Abort
+#[bitbybit::bitfield(u32, defmt_bitfields(feature = "defmt"))]
+pub struct PrefetchAbortIss {
+    /// External abort type (IMPLEMENTATION DEFINED)
+    #[bit(9, r)]
+    ea: bool,
+    /// Was the fault on a stage 2 translation for a stage 1 table walk?
+    #[bit(7, r)]
+    s1ptw: bool,
+    /// Instruction Fault Status Code
+    #[bits(0..=5, r)]
+    ifsc_raw: u6,
+}
+
+impl PrefetchAbortIss {
+    /// Decode the Instruction Fault Status Code
+    pub fn ifsc(&self) -> Result<FaultStatusCode, u8> {
+        FaultStatusCode::try_from(self.ifsc_raw().as_u8())
+    }
+}
+
+impl core::fmt::Debug for PrefetchAbortIss {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PrefetchAbortIss")
+            .field("ea", &self.ea())
+            .field("s1ptw", &self.s1ptw())
+            .field("ifsc", &self.ifsc())
+            .finish()
+    }
+}
+
+/// ISS for a Data Abort
+#[bitbybit::bitfield(u32, defmt_bitfields(feature = "defmt"))]
+pub struct DataAbortIss {
+    /// Instruction Syndrome Valid
+    ///
+    /// Only when this is `true` are `sas`, `sse` and `srt` valid.
+    #[bit(24, r)]
+    isv: bool,
+    /// Syndrome Access Size
+    #[bits(22..=23, r)]
+    sas: AccessSize,
+    /// Syndrome Sign Extend - was a load sign-extended?
+    #[bit(21, r)]
+    sse: bool,
+    /// Syndrome Register Transfer - the register that was the target of
+    /// the load, or the source of the store
+    #[bits(16..=19, r)]
+    srt: u4,
+    /// External abort type (IMPLEMENTATION DEFINED)
+    #[bit(9, r)]
+    ea: bool,
+    /// Was the fault on a cache maintenance operation?
+    #[bit(8, r)]
+    cm: bool,
+    /// Was the fault on a stage 2 translation for a stage 1 table walk?
+    #[bit(7, r)]
+    s1ptw: bool,
+    /// Write not Read - `true` if the abort was caused by a write
+    #[bit(6, r)]
+    wnr: bool,
+    /// Data Fault Status Code
+    #[bits(0..=5, r)]
+    dfsc_raw: u6,
+}
+
+impl DataAbortIss {
+    /// Decode the Data Fault Status Code
+    pub fn dfsc(&self) -> Result<FaultStatusCode, u8> {
+        FaultStatusCode::try_from(self.dfsc_raw().as_u8())
+    }
+}
+
+impl core::fmt::Debug for DataAbortIss {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DataAbortIss")
+            .field("isv", &self.isv())
+            .field("sas", &self.sas())
+            .field("sse", &self.sse())
+            .field("srt", &self.srt())
+            .field("ea", &self.ea())
+            .field("cm", &self.cm())
+            .field("s1ptw", &self.s1ptw())
+            .field("wnr", &self.wnr())
+            .field("dfsc", &self.dfsc())
+            .finish()
+    }
+}
+
+/// The size of the access that caused a Data Abort
+#[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Hsr(pub u32);
+#[bitbybit::bitenum(u2, exhaustive = true)]
+pub enum AccessSize {
+    /// 8-bit access
+    Byte = 0b00,
+    /// 16-bit access
+    Halfword = 0b01,
+    /// 32-bit access
+    Word = 0b10,
+    /// 64-bit access
+    Doubleword = 0b11,
+}
+
+/// Fault Status Code for a Data Abort or Prefetch Abort taken to Hyp mode
+///
+/// HSR always uses the long-descriptor encoding. Where there is a `level`,
+/// it is the level of lookup at which the fault occurred.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FaultStatusCode {
+    /// Address size fault
+    AddressSize { level: u2 },
+    /// Translation fault
+    Translation { level: u2 },
+    /// Access flag fault
+    AccessFlag { level: u2 },
+    /// Permission fault
+    Permission { level: u2 },
+    /// Synchronous external abort, not on a translation table walk
+    SyncExternalAbort,
+    /// Asynchronous external abort
+    AsyncExternalAbort,
+    /// Synchronous external abort on a translation table walk
+    SyncExternalAbortOnTableWalk { level: u2 },
+    /// Synchronous parity or ECC error, not on a translation table walk
+    SyncParityError,
+    /// Asynchronous parity or ECC error
+    AsyncParityError,
+    /// Synchronous parity or ECC error on a translation table walk
+    SyncParityErrorOnTableWalk { level: u2 },
+    /// Alignment fault
+    Alignment,
+    /// Debug event
+    DebugEvent,
+    /// TLB conflict abort
+    TlbConflict,
+    /// Lockdown (IMPLEMENTATION DEFINED)
+    Lockdown,
+    /// Unsupported exclusive access
+    UnsupportedExclusive,
+    /// Coprocessor abort (IMPLEMENTATION DEFINED)
+    CoprocessorAbort,
+}
+
+impl TryFrom<u8> for FaultStatusCode {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let level = u2::new(value & 0b11);
+        match value {
+            0b000000..=0b000011 => Ok(FaultStatusCode::AddressSize { level }),
+            0b000100..=0b000111 => Ok(FaultStatusCode::Translation { level }),
+            0b001000..=0b001011 => Ok(FaultStatusCode::AccessFlag { level }),
+            0b001100..=0b001111 => Ok(FaultStatusCode::Permission { level }),
+            0b010000 => Ok(FaultStatusCode::SyncExternalAbort),
+            0b010001 => Ok(FaultStatusCode::AsyncExternalAbort),
+            0b010100..=0b010111 => Ok(FaultStatusCode::SyncExternalAbortOnTableWalk { level }),
+            0b011000 => Ok(FaultStatusCode::SyncParityError),
+            0b011001 => Ok(FaultStatusCode::AsyncParityError),
+            0b011100..=0b011111 => Ok(FaultStatusCode::SyncParityErrorOnTableWalk { level }),
+            0b100001 => Ok(FaultStatusCode::Alignment),
+            0b100010 => Ok(FaultStatusCode::DebugEvent),
+            0b110000 => Ok(FaultStatusCode::TlbConflict),
+            0b110100 => Ok(FaultStatusCode::Lockdown),
+            0b110101 => Ok(FaultStatusCode::UnsupportedExclusive),
+            0b111010 => Ok(FaultStatusCode::CoprocessorAbort),
+            _ => Err(value),
+        }
+    }
+}
 
 impl SysReg for Hsr {
     const CP: u32 = 15;
@@ -22,7 +398,7 @@ impl Hsr {
     #[inline]
     /// Reads HSR (*Hyp Syndrome Register*)
     pub fn read() -> Hsr {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +413,72 @@ impl Hsr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn data_abort() {
+        // DABT from EL1, IL=1, ISV=1, SAS=word, SRT=r3, WnR=1, DFSC=permission fault level 0
+        let hsr = Hsr::new_with_raw_value(0x9383_004C);
+        assert_eq!(hsr.ec(), Ok(ExceptionClass::DataAbortFromLower));
+        assert!(hsr.il());
+        let Iss::DataAbort(iss) = hsr.decode_iss() else {
+            panic!("not a data abort: {:?}", hsr);
+        };
+        assert!(iss.isv());
+        assert_eq!(iss.sas(), AccessSize::Word);
+        assert_eq!(iss.srt(), u4::new(3));
+        assert!(iss.wnr());
+        assert_eq!(
+            iss.dfsc(),
+            Ok(FaultStatusCode::Permission { level: u2::new(0) })
+        );
+    }
+
+    #[test]
+    fn cp15_trap() {
+        // MRC p15, 0, r2, c1, c0, 0 (a read of SCTLR), unconditional
+        let hsr = Hsr::new_with_raw_value(0x0FE0_0441);
+        assert_eq!(hsr.ec(), Ok(ExceptionClass::TrappedCp15McrMrc));
+        let Iss::Cp15Trap(iss) = hsr.decode_iss() else {
+            panic!("not a CP15 trap: {:?}", hsr);
+        };
+        assert!(iss.cv());
+        assert_eq!(iss.cond(), u4::new(0xE));
+        assert_eq!(iss.opc1(), u3::new(0));
+        assert_eq!(iss.opc2(), u3::new(0));
+        assert_eq!(iss.crn(), u4::new(1));
+        assert_eq!(iss.crm(), u4::new(0));
+        assert_eq!(iss.rt(), u4::new(2));
+        assert!(iss.is_read());
+    }
+
+    #[test]
+    fn hvc_and_svc() {
+        // HVC #0x1234, IL=1
+        let hsr = Hsr::new_with_raw_value((0x12 << 26) | (1 << 25) | 0x1234);
+        let Iss::Hvc(iss) = hsr.decode_iss() else {
+            panic!("not an HVC: {:?}", hsr);
+        };
+        assert_eq!(iss.imm16(), 0x1234);
+        // SVC #0x56 routed to Hyp mode, IL=1
+        let hsr = Hsr::new_with_raw_value((0x11 << 26) | (1 << 25) | 0x56);
+        let Iss::Svc(iss) = hsr.decode_iss() else {
+            panic!("not an SVC: {:?}", hsr);
+        };
+        assert_eq!(iss.imm16(), 0x56);
+    }
+
+    #[test]
+    fn reserved_ec() {
+        let hsr = Hsr::new_with_raw_value(0x3F << 26);
+        assert!(hsr.ec().is_err());
+        assert!(matches!(hsr.decode_iss(), Iss::Other(_)));
+    }
+}
@@ -0,0 +1,33 @@
+//! ATS12NSOPW (*Stages 1 and 2 Non-secure only PL1 Write*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+/// ATS12NSOPW (*Stages 1 and 2 Non-secure only PL1 Write*)
+///
+/// The result of the operation is written to [`Par`](crate::register::Par).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ats12nsopw;
+
+impl SysReg for Ats12nsopw {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegWrite for Ats12nsopw {}
+
+impl Ats12nsopw {
+    #[inline]
+    /// Writes ATS12NSOPW (*Stages 1 and 2 Non-secure only PL1 Write*)
+    ///
+    /// The value is the virtual address to translate.
+    pub fn write(va: u32) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(va);
+        }
+    }
+}
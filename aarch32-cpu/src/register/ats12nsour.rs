@@ -0,0 +1,33 @@
+//! ATS12NSOUR (*Stages 1 and 2 Non-secure only Unprivileged Read*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+/// ATS12NSOUR (*Stages 1 and 2 Non-secure only Unprivileged Read*)
+///
+/// The result of the operation is written to [`Par`](crate::register::Par).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ats12nsour;
+
+impl SysReg for Ats12nsour {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 6;
+}
+
+impl crate::register::SysRegWrite for Ats12nsour {}
+
+impl Ats12nsour {
+    #[inline]
+    /// Writes ATS12NSOUR (*Stages 1 and 2 Non-secure only Unprivileged Read*)
+    ///
+    /// The value is the virtual address to translate.
+    pub fn write(va: u32) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(va);
+        }
+    }
+}
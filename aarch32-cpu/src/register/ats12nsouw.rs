@@ -0,0 +1,33 @@
+//! ATS12NSOUW (*Stages 1 and 2 Non-secure only Unprivileged Write*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+/// ATS12NSOUW (*Stages 1 and 2 Non-secure only Unprivileged Write*)
+///
+/// The result of the operation is written to [`Par`](crate::register::Par).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ats12nsouw;
+
+impl SysReg for Ats12nsouw {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegWrite for Ats12nsouw {}
+
+impl Ats12nsouw {
+    #[inline]
+    /// Writes ATS12NSOUW (*Stages 1 and 2 Non-secure only Unprivileged Write*)
+    ///
+    /// The value is the virtual address to translate.
+    pub fn write(va: u32) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(va);
+        }
+    }
+}
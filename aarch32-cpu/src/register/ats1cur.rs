@@ -0,0 +1,33 @@
+//! ATS1CUR (*Stage 1 Current state Unprivileged Read*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+/// ATS1CUR (*Stage 1 Current state Unprivileged Read*)
+///
+/// The result of the operation is written to [`Par`](crate::register::Par).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ats1cur;
+
+impl SysReg for Ats1cur {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegWrite for Ats1cur {}
+
+impl Ats1cur {
+    #[inline]
+    /// Writes ATS1CUR (*Stage 1 Current state Unprivileged Read*)
+    ///
+    /// The value is the virtual address to translate.
+    pub fn write(va: u32) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(va);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! BPIALLIS: Invalidate all entries from branch predictors, Inner Shareable
+
+use crate::register::SysReg;
+
+/// BPIALLIS: Invalidate all entries from branch predictors, Inner Shareable
+///
+/// Like [`BpIAll`](crate::register::BpIAll), but for every processor in the
+/// Inner Shareable domain. Needs the Multiprocessing Extensions.
+pub struct BpIAllIs;
+
+impl SysReg for BpIAllIs {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 1;
+    const OP2: u32 = 6;
+}
+
+impl crate::register::SysRegWrite for BpIAllIs {}
+
+impl BpIAllIs {
+    #[inline]
+    pub fn write() {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(0) }
+    }
+}
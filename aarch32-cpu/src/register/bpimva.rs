@@ -0,0 +1,23 @@
+//! BPIMVA: Invalidate an address from branch predictors
+
+use crate::register::SysReg;
+
+/// BPIMVA: Invalidate an address from branch predictors
+pub struct BpIMva;
+
+impl SysReg for BpIMva {
+    const CP: u32 = 15;
+    const CRN: u32 = 7;
+    const OP1: u32 = 0;
+    const CRM: u32 = 5;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegWrite for BpIMva {}
+
+impl BpIMva {
+    #[inline]
+    pub fn write(addr: u32) {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(addr) }
+    }
+}
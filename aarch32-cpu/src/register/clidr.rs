@@ -1,7 +1,38 @@
 //! Code for managing CLIDR (*Cache Level ID Register*)
 
+use arbitrary_int::u3;
+
 use crate::register::{SysReg, SysRegRead};
 
+/// The caches implemented at one level, from a CLIDR Ctype field
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CacheKind {
+    /// No cache
+    None,
+    /// Instruction cache only
+    Instruction,
+    /// Data cache only
+    Data,
+    /// Separate instruction and data caches
+    Separate,
+    /// Unified cache
+    Unified,
+    /// A reserved encoding
+    Reserved(u8),
+}
+
+impl CacheKind {
+    /// Does this level hold data which may need cleaning or invalidating?
+    pub const fn has_data(&self) -> bool {
+        matches!(
+            self,
+            CacheKind::Data | CacheKind::Separate | CacheKind::Unified
+        )
+    }
+}
+
 /// CLIDR (*Cache Level ID Register*)
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -25,3 +56,41 @@ impl Clidr {
         unsafe { Self(<Self as SysRegRead>::read_raw()) }
     }
 }
+
+impl Clidr {
+    /// The caches implemented at a level
+    ///
+    /// Levels are numbered from zero (for L1), as in [`Csselr`](crate::register::Csselr).
+    /// CLIDR only describes levels 0 to 6, so level 7 has no cache.
+    pub const fn cache_kind(&self, level: u3) -> CacheKind {
+        if level.value() == 7 {
+            return CacheKind::None;
+        }
+        match (self.0 >> (3 * level.value() as u32)) & 0b111 {
+            0b000 => CacheKind::None,
+            0b001 => CacheKind::Instruction,
+            0b010 => CacheKind::Data,
+            0b011 => CacheKind::Separate,
+            0b100 => CacheKind::Unified,
+            other => CacheKind::Reserved(other as u8),
+        }
+    }
+
+    /// Level of Unification Inner Shareable
+    pub const fn louis(&self) -> u3 {
+        u3::new(((self.0 >> 21) & 0b111) as u8)
+    }
+
+    /// Level of Coherency
+    ///
+    /// Levels below this one (counting from zero) must be cleaned to make
+    /// data visible to every observer.
+    pub const fn loc(&self) -> u3 {
+        u3::new(((self.0 >> 24) & 0b111) as u8)
+    }
+
+    /// Level of Unification Uniprocessor
+    pub const fn louu(&self) -> u3 {
+        u3::new(((self.0 >> 27) & 0b111) as u8)
+    }
+}
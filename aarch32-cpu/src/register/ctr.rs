@@ -25,3 +25,29 @@ impl Ctr {
         unsafe { Self(<Self as SysRegRead>::read_raw()) }
     }
 }
+
+impl Ctr {
+    /// log2 of the number of words in the smallest data or unified cache line
+    ///
+    /// This is the DminLine field of the Armv7 CTR format.
+    pub const fn dminline(&self) -> u32 {
+        (self.0 >> 16) & 0xF
+    }
+
+    /// log2 of the number of words in the smallest instruction cache line
+    ///
+    /// This is the IminLine field of the Armv7 CTR format.
+    pub const fn iminline(&self) -> u32 {
+        self.0 & 0xF
+    }
+
+    /// The smallest data or unified cache line, in bytes
+    pub const fn dcache_line_size(&self) -> usize {
+        4 << self.dminline()
+    }
+
+    /// The smallest instruction cache line, in bytes
+    pub const fn icache_line_size(&self) -> usize {
+        4 << self.iminline()
+    }
+}
@@ -11,7 +11,7 @@ pub struct Dacr {
 }
 
 /// Domain Access Permissions
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[bitbybit::bitenum(u2, exhaustive = true)]
@@ -66,3 +66,23 @@ impl Dacr {
         Self::write(value);
     }
 }
+
+impl Dacr {
+    /// Create a value with every domain set to `access`
+    pub const fn new_with_all(access: DomainAccess) -> Dacr {
+        Dacr::new_with_raw_value(0).with_domains(0xFFFF, access)
+    }
+
+    /// Set the access for every domain whose bit is set in `mask`
+    pub const fn with_domains(self, mask: u16, access: DomainAccess) -> Dacr {
+        let mut value = self;
+        let mut i = 0;
+        while i < 16 {
+            if mask & (1 << i) != 0 {
+                value = value.with_d(i, access);
+            }
+            i += 1;
+        }
+        value
+    }
+}
@@ -13,7 +13,7 @@ pub struct Dracr {
     nx: bool,
     /// Access Permission bits
     #[bits(8..=10, rw)]
-    ap: u3,
+    ap: AccessPerms,
     /// TEX bits
     #[bits(3..=5, rw)]
     tex: u3,
@@ -28,6 +28,28 @@ pub struct Dracr {
     b: bool,
 }
 
+/// Access permissions for a PMSAv7 MPU region
+#[derive(Debug, PartialEq, Eq)]
+#[bitbybit::bitenum(u3, exhaustive = true)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessPerms {
+    /// No access from any mode
+    NoAccess = 0b000,
+    /// Read/write at PL1, no access from user mode
+    PrivilegedOnly = 0b001,
+    /// Read/write at PL1, read-only from user mode
+    NoUserWrite = 0b010,
+    /// Read/write from any mode
+    FullAccess = 0b011,
+    _Reserved1 = 0b100,
+    /// Read-only at PL1, no access from user mode
+    PrivilegedReadOnly = 0b101,
+    /// Read-only from any mode
+    ReadOnly = 0b110,
+    _Reserved2 = 0b111,
+}
+
 impl SysReg for Dracr {
     const CP: u32 = 15;
     const CRN: u32 = 6;
@@ -4,6 +4,8 @@ use arbitrary_int::u3;
 
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
+use super::dracr::AccessPerms;
+
 /// IRACR (*Instruction Region Access Control Register*)
 #[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -13,7 +15,7 @@ pub struct Iracr {
     nx: bool,
     /// Access Permission bits
     #[bits(8..=10, rw)]
-    ap: u3,
+    ap: AccessPerms,
     /// TEX bits
     #[bits(3..=5, rw)]
     tex: u3,
@@ -41,6 +41,189 @@ impl Midr {
         // Safety: Reading this register has no side-effects and is atomic
         unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
+
+    /// Identify which processor this MIDR value belongs to
+    pub fn part(&self) -> CpuPart {
+        CpuPart::from_ids(self.implementer(), self.part_no().value())
+    }
+
+    /// Get the `rNpM` revision of the processor
+    pub fn revision(&self) -> Revision {
+        Revision {
+            variant: self.variant().value(),
+            revision: self.rev().value(),
+        }
+    }
+}
+
+/// The Arm Limited implementer code
+const IMPLEMENTER_ARM: u8 = 0x41;
+
+/// A processor we know how to identify from its MIDR
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CpuPart {
+    /// ARM926EJ-S
+    Arm926,
+    /// ARM1176JZF-S
+    Arm1176,
+    /// Cortex-A5
+    CortexA5,
+    /// Cortex-A7
+    CortexA7,
+    /// Cortex-A8
+    CortexA8,
+    /// Cortex-A9
+    CortexA9,
+    /// Cortex-A15
+    CortexA15,
+    /// Cortex-A17
+    CortexA17,
+    /// Cortex-R4
+    CortexR4,
+    /// Cortex-R5
+    CortexR5,
+    /// Cortex-R7
+    CortexR7,
+    /// Cortex-R8
+    CortexR8,
+    /// Cortex-R52
+    CortexR52,
+    /// Cortex-R82
+    CortexR82,
+    /// Some other processor
+    Unknown {
+        /// The MIDR implementer code
+        implementer: u8,
+        /// The MIDR primary part number
+        part_no: u16,
+    },
+}
+
+/// Static facts about a known processor
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PartInfo {
+    /// Human readable name, e.g. `"Cortex-R5"`
+    pub name: &'static str,
+    /// Size of an L1 cache line in bytes
+    pub cache_line_size: usize,
+    /// Can this processor have Tightly Coupled Memories?
+    pub has_tcm: bool,
+    /// Can this processor have a Snoop Control Unit (in multi-core configurations)?
+    pub has_scu: bool,
+}
+
+impl CpuPart {
+    /// Map an implementer code and primary part number to a known processor
+    pub const fn from_ids(implementer: u8, part_no: u16) -> CpuPart {
+        if implementer != IMPLEMENTER_ARM {
+            return CpuPart::Unknown {
+                implementer,
+                part_no,
+            };
+        }
+        match part_no {
+            0x926 => CpuPart::Arm926,
+            0xB76 => CpuPart::Arm1176,
+            0xC05 => CpuPart::CortexA5,
+            0xC07 => CpuPart::CortexA7,
+            0xC08 => CpuPart::CortexA8,
+            0xC09 => CpuPart::CortexA9,
+            0xC0F => CpuPart::CortexA15,
+            0xC0E => CpuPart::CortexA17,
+            0xC14 => CpuPart::CortexR4,
+            0xC15 => CpuPart::CortexR5,
+            0xC17 => CpuPart::CortexR7,
+            0xC18 => CpuPart::CortexR8,
+            0xD13 => CpuPart::CortexR52,
+            0xD15 => CpuPart::CortexR82,
+            _ => CpuPart::Unknown {
+                implementer,
+                part_no,
+            },
+        }
+    }
+
+    /// Get the static description of this processor, if it is a known one
+    pub const fn info(&self) -> Option<PartInfo> {
+        const fn info(
+            name: &'static str,
+            cache_line_size: usize,
+            has_tcm: bool,
+            has_scu: bool,
+        ) -> Option<PartInfo> {
+            Some(PartInfo {
+                name,
+                cache_line_size,
+                has_tcm,
+                has_scu,
+            })
+        }
+        match self {
+            CpuPart::Arm926 => info("ARM926EJ-S", 32, true, false),
+            CpuPart::Arm1176 => info("ARM1176JZF-S", 32, true, false),
+            CpuPart::CortexA5 => info("Cortex-A5", 32, false, true),
+            CpuPart::CortexA7 => info("Cortex-A7", 64, false, true),
+            CpuPart::CortexA8 => info("Cortex-A8", 64, false, false),
+            CpuPart::CortexA9 => info("Cortex-A9", 32, false, true),
+            CpuPart::CortexA15 => info("Cortex-A15", 64, false, true),
+            CpuPart::CortexA17 => info("Cortex-A17", 64, false, true),
+            CpuPart::CortexR4 => info("Cortex-R4", 32, true, false),
+            CpuPart::CortexR5 => info("Cortex-R5", 32, true, false),
+            CpuPart::CortexR7 => info("Cortex-R7", 32, true, true),
+            CpuPart::CortexR8 => info("Cortex-R8", 32, true, true),
+            CpuPart::CortexR52 => info("Cortex-R52", 64, true, false),
+            CpuPart::CortexR82 => info("Cortex-R82", 64, true, false),
+            CpuPart::Unknown { .. } => None,
+        }
+    }
+}
+
+impl core::fmt::Display for CpuPart {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CpuPart::Unknown {
+                implementer,
+                part_no,
+            } => write!(f, "Unknown (0x{:02x}/0x{:03x})", implementer, part_no),
+            known => f.write_str(known.info().map_or("", |info| info.name)),
+        }
+    }
+}
+
+/// A processor revision, as printed in the `rNpM` form used by Arm
+///
+/// Revisions are ordered, so `rev >= Revision::new(2, 1)` is a valid errata check.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Revision {
+    /// The major revision (the `N` in `rNpM`)
+    pub variant: u8,
+    /// The minor revision (the `M` in `rNpM`)
+    pub revision: u8,
+}
+
+impl Revision {
+    /// Create a revision `r{variant}p{revision}`
+    pub const fn new(variant: u8, revision: u8) -> Revision {
+        Revision { variant, revision }
+    }
+}
+
+impl core::fmt::Display for Revision {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "r{}p{}", self.variant, self.revision)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Revision {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "r{=u8}p{=u8}", self.variant, self.revision)
+    }
 }
 
 impl core::fmt::Debug for Midr {
@@ -56,3 +239,30 @@ impl defmt::Format for Midr {
         defmt::write!(f, "MIDR {{ implementer=0x{0=24..32:02x} variant=0x{0=20..24:x} arch=0x{0=16..20:x} part_no=0x{0=4..16:03x} rev=0x{0=0..4:x} }}", self.raw_value())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cortex_r5() {
+        let midr = Midr::new_with_raw_value(0x411F_C153);
+        assert_eq!(midr.part(), CpuPart::CortexR5);
+        assert_eq!(midr.revision(), Revision::new(1, 3));
+        assert!(midr.revision() > Revision::new(0, 5));
+        assert!(midr.part().info().unwrap().has_tcm);
+    }
+
+    #[test]
+    fn unknown_part() {
+        let midr = Midr::new_with_raw_value(0x560F_5811);
+        assert_eq!(
+            midr.part(),
+            CpuPart::Unknown {
+                implementer: 0x56,
+                part_no: 0x581
+            }
+        );
+        assert_eq!(midr.part().info(), None);
+    }
+}
@@ -7,7 +7,17 @@ pub mod aidr;
 pub mod aifsr;
 pub mod amair0;
 pub mod amair1;
+pub mod ats12nsopr;
+pub mod ats12nsopw;
+pub mod ats12nsour;
+pub mod ats12nsouw;
+pub mod ats1cpr;
+pub mod ats1cpw;
+pub mod ats1cur;
+pub mod ats1cuw;
 pub mod bpiall;
+pub mod bpiallis;
+pub mod bpimva;
 pub mod ccsidr;
 pub mod clidr;
 pub mod contextidr;
@@ -34,6 +44,7 @@ pub mod dspsr;
 pub mod fcseidr;
 pub mod icc_pmr;
 pub mod iciallu;
+pub mod icimvau;
 pub mod id_afr0;
 pub mod id_dfr0;
 pub mod id_isar0;
@@ -60,6 +71,7 @@ pub mod mair1;
 pub mod midr;
 pub mod mpidr;
 pub mod mpuir;
+pub mod nmrr;
 pub mod nsacr;
 pub mod par;
 pub mod pmccfiltr;
@@ -86,17 +98,24 @@ pub mod pmswinc;
 pub mod pmuserenr;
 pub mod pmxevcntr;
 pub mod pmxevtyper;
+pub mod prrr;
 pub mod revidr;
 pub mod rgnr;
 pub mod rvbar;
 pub mod sctlr;
 pub mod tcmtr;
 pub mod tlbiall;
+pub mod tlbiallis;
+pub mod tlbiasid;
 pub mod tlbtr;
 pub mod tpidrprw;
 pub mod tpidruro;
 pub mod tpidrurw;
+pub mod ttbcr;
 pub mod ttbr0;
+pub mod ttbr0_lpae;
+pub mod ttbr1;
+pub mod ttbr1_lpae;
 pub mod vmpidr;
 pub mod vpidr;
 pub mod vsctlr;
@@ -108,7 +127,17 @@ pub use aidr::Aidr;
 pub use aifsr::Aifsr;
 pub use amair0::Amair0;
 pub use amair1::Amair1;
+pub use ats12nsopr::Ats12nsopr;
+pub use ats12nsopw::Ats12nsopw;
+pub use ats12nsour::Ats12nsour;
+pub use ats12nsouw::Ats12nsouw;
+pub use ats1cpr::Ats1cpr;
+pub use ats1cpw::Ats1cpw;
+pub use ats1cur::Ats1cur;
+pub use ats1cuw::Ats1cuw;
 pub use bpiall::BpIAll;
+pub use bpiallis::BpIAllIs;
+pub use bpimva::BpIMva;
 pub use ccsidr::Ccsidr;
 pub use clidr::Clidr;
 pub use contextidr::Contextidr;
@@ -134,6 +163,7 @@ pub use dspsr::Dspsr;
 pub use fcseidr::Fcseidr;
 pub use icc_pmr::IccPmr;
 pub use iciallu::Iciallu;
+pub use icimvau::Icimvau;
 pub use id_afr0::IdAfr0;
 pub use id_dfr0::IdDfr0;
 pub use id_isar0::IdIsar0;
@@ -156,9 +186,10 @@ pub use irbar::Irbar;
 pub use irsr::Irsr;
 pub use mair0::Mair0;
 pub use mair1::Mair1;
-pub use midr::Midr;
+pub use midr::{CpuPart, Midr, Revision};
 pub use mpidr::Mpidr;
 pub use mpuir::Mpuir;
+pub use nmrr::Nmrr;
 pub use nsacr::Nsacr;
 pub use par::Par;
 pub use pmccfiltr::Pmccfiltr;
@@ -185,24 +216,31 @@ pub use pmswinc::Pmswinc;
 pub use pmuserenr::Pmuserenr;
 pub use pmxevcntr::Pmxevcntr;
 pub use pmxevtyper::Pmxevtyper;
+pub use prrr::{Prrr, RemapMemoryType};
 pub use revidr::Revidr;
 pub use rgnr::Rgnr;
 pub use rvbar::Rvbar;
 pub use sctlr::Sctlr;
 pub use tcmtr::Tcmtr;
 pub use tlbiall::TlbIAll;
+pub use tlbiallis::TlbIAllIs;
+pub use tlbiasid::TlbIAsid;
 pub use tlbtr::Tlbtr;
 pub use tpidrprw::Tpidrprw;
 pub use tpidruro::Tpidruro;
 pub use tpidrurw::Tpidrurw;
+pub use ttbcr::Ttbcr;
 pub use ttbr0::Ttbr0;
+pub use ttbr0_lpae::Ttbr0Lpae;
+pub use ttbr1::Ttbr1;
+pub use ttbr1_lpae::Ttbr1Lpae;
 pub use vmpidr::Vmpidr;
 pub use vpidr::Vpidr;
 pub use vsctlr::Vsctlr;
 
-#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
 pub mod armv8r;
-#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+#[cfg(any(test, doc, not(target_arch = "arm"), arm_architecture = "v8-r"))]
 pub use armv8r::*;
 
 pub use imp::*;
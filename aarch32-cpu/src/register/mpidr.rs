@@ -24,4 +24,13 @@ impl Mpidr {
     pub fn read() -> Mpidr {
         unsafe { Self(<Self as SysRegRead>::read_raw()) }
     }
+
+    /// Does the processor implement the Multiprocessing Extensions?
+    ///
+    /// These add the Inner Shareable TLB and branch predictor maintenance
+    /// operations, such as [`TlbIAllIs`](crate::register::TlbIAllIs).
+    #[inline]
+    pub const fn mp_extensions(&self) -> bool {
+        self.0 & (1 << 31) != 0
+    }
 }
@@ -0,0 +1,56 @@
+//! Code for managing NMRR (*Normal Memory Remap Register*)
+
+use crate::{
+    mmu::CacheableMemoryAttribute,
+    register::{SysReg, SysRegRead, SysRegWrite},
+};
+
+/// NMRR (*Normal Memory Remap Register*)
+///
+/// Only used when the TEX remap is enabled with `SCTLR.TRE`, and only for
+/// regions that [`Prrr`](crate::register::Prrr) marks as Normal memory. This
+/// register shares its encoding with MAIR1.
+#[bitbybit::bitfield(u32, default = 0, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nmrr {
+    /// Outer cacheability, for each of the eight regions
+    #[bits(16..=17, rw)]
+    or: [CacheableMemoryAttribute; 8],
+    /// Inner cacheability, for each of the eight regions
+    #[bits(0..=1, rw)]
+    ir: [CacheableMemoryAttribute; 8],
+}
+
+impl SysReg for Nmrr {
+    const CP: u32 = 15;
+    const CRN: u32 = 10;
+    const OP1: u32 = 0;
+    const CRM: u32 = 2;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegRead for Nmrr {}
+
+impl Nmrr {
+    #[inline]
+    /// Reads NMRR (*Normal Memory Remap Register*)
+    pub fn read() -> Nmrr {
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
+    }
+}
+
+impl crate::register::SysRegWrite for Nmrr {}
+
+impl Nmrr {
+    #[inline]
+    /// Writes NMRR (*Normal Memory Remap Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
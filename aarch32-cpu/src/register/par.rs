@@ -1,12 +1,81 @@
 //! Code for managing PAR (*Physical Address Register*)
 
+use arbitrary_int::{u20, u5};
+
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
-/// PAR (*Physical Address Register*)
-#[derive(Debug, Clone, Copy)]
+/// Inner memory attributes reported in PAR
+#[bitbybit::bitenum(u3, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParInnerAttributes {
+    NonCacheable = 0b000,
+    StronglyOrdered = 0b001,
+    _Reserved1 = 0b010,
+    Device = 0b011,
+    _Reserved2 = 0b100,
+    WriteBackWriteAllocate = 0b101,
+    WriteThrough = 0b110,
+    WriteBackNoWriteAllocate = 0b111,
+}
+
+/// Outer memory attributes reported in PAR
+#[bitbybit::bitenum(u2, exhaustive = true)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Par(pub u32);
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParOuterAttributes {
+    NonCacheable = 0b00,
+    WriteBackWriteAllocate = 0b01,
+    WriteThrough = 0b10,
+    WriteBackNoWriteAllocate = 0b11,
+}
+
+/// PAR (*Physical Address Register*)
+///
+/// This is the Short-descriptor format of the register. When [`Par::f`] is
+/// set, the translation failed and only the fault fields are valid; otherwise
+/// only the address and attribute fields are valid.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Par {
+    /// Physical Address bits `[31:12]`
+    ///
+    /// For a Supersection, bits `[23:16]` of this field hold `PA[39:32]` instead.
+    #[bits(12..=31, rw)]
+    pa: u20,
+    /// Set if the Long-descriptor format is in use
+    #[bit(11, rw)]
+    lpae: bool,
+    /// Not Outer Shareable
+    #[bit(10, rw)]
+    nos: bool,
+    /// Non-secure
+    #[bit(9, rw)]
+    ns: bool,
+    /// Shareable
+    #[bit(7, rw)]
+    sh: bool,
+    /// Inner memory attributes
+    #[bits(4..=6, rw)]
+    inner: ParInnerAttributes,
+    /// Outer memory attributes
+    #[bits(2..=3, rw)]
+    outer: ParOuterAttributes,
+    /// Set if the translation used a Supersection
+    #[bit(1, rw)]
+    ss: bool,
+    /// External abort qualifier (aborted translations only)
+    #[bit(6, rw)]
+    fault_ext: bool,
+    /// Fault status, in the DFSR encoding (aborted translations only)
+    #[bits([1..=4, 5], rw)]
+    fault_status_raw: u5,
+    /// Set if the translation aborted
+    #[bit(0, rw)]
+    f: bool,
+}
 
 impl SysReg for Par {
     const CP: u32 = 15;
@@ -22,7 +91,7 @@ impl Par {
     #[inline]
     /// Reads PAR (*Physical Address Register*)
     pub fn read() -> Par {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +106,7 @@ impl Par {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
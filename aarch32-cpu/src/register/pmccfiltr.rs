@@ -3,10 +3,25 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMCCFILTR (*Performance Monitors Cycle Count Filter Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmccfiltr(pub u32);
+pub struct Pmccfiltr {
+    /// Privileged filtering - when set, do not count cycles at EL1
+    #[bit(31, rw)]
+    p: bool,
+    /// User filtering - when set, do not count cycles at EL0
+    #[bit(30, rw)]
+    u: bool,
+    /// Non-secure EL1 filtering
+    #[bit(29, rw)]
+    nsk: bool,
+    /// Non-secure EL0 filtering
+    #[bit(28, rw)]
+    nsu: bool,
+    /// Non-secure EL2 filtering - when set, count cycles at EL2
+    #[bit(27, rw)]
+    nsh: bool,
+}
 
 impl SysReg for Pmccfiltr {
     const CP: u32 = 15;
@@ -22,7 +37,7 @@ impl Pmccfiltr {
     #[inline]
     /// Reads PMCCFILTR (*Performance Monitors Cycle Count Filter Register*)
     pub fn read() -> Pmccfiltr {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +52,7 @@ impl Pmccfiltr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
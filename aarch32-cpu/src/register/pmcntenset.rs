@@ -3,10 +3,16 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMCNTENSET (*Performance Monitors Count Enable Set Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmcntenset(pub u32);
+pub struct Pmcntenset {
+    /// Cycle counter (PMCCNTR) enable
+    #[bit(31, rw)]
+    c: bool,
+    /// Event counter (`PMEVCNTR<n>`) enable
+    #[bit(0, rw)]
+    p: [bool; 31],
+}
 
 impl SysReg for Pmcntenset {
     const CP: u32 = 15;
@@ -22,7 +28,7 @@ impl Pmcntenset {
     #[inline]
     /// Reads PMCNTENSET (*Performance Monitors Count Enable Set Register*)
     pub fn read() -> Pmcntenset {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +43,7 @@ impl Pmcntenset {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
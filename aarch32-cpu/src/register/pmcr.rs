@@ -1,12 +1,44 @@
 //! Code for managing PMCR (*Performance Monitors Control Register*)
 
+use arbitrary_int::u5;
+
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMCR (*Performance Monitors Control Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmcr(pub u32);
+pub struct Pmcr {
+    /// Implementer code
+    #[bits(24..=31, r)]
+    imp: u8,
+    /// Identification code
+    #[bits(16..=23, r)]
+    idcode: u8,
+    /// Number of event counters implemented
+    #[bits(11..=15, r)]
+    n: u5,
+    /// Long cycle counter enable (Armv8 only)
+    #[bit(6, rw)]
+    lc: bool,
+    /// Disable cycle counter when event counting is prohibited
+    #[bit(5, rw)]
+    dp: bool,
+    /// Export enable
+    #[bit(4, rw)]
+    x: bool,
+    /// Clock divider - when set, the cycle counter counts every 64th cycle
+    #[bit(3, rw)]
+    d: bool,
+    /// Cycle counter reset (write-only, reads as zero)
+    #[bit(2, rw)]
+    c: bool,
+    /// Event counter reset (write-only, reads as zero)
+    #[bit(1, rw)]
+    p: bool,
+    /// Enable all counters
+    #[bit(0, rw)]
+    e: bool,
+}
 
 impl SysReg for Pmcr {
     const CP: u32 = 15;
@@ -22,7 +54,7 @@ impl Pmcr {
     #[inline]
     /// Reads PMCR (*Performance Monitors Control Register*)
     pub fn read() -> Pmcr {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +69,24 @@ impl Pmcr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+
+    /// Modify PMCR (*Performance Monitors Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// See docs for [Self::write].
+    #[inline]
+    pub unsafe fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        unsafe {
+            Self::write(value);
         }
     }
 }
@@ -3,10 +3,28 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMEVTYPER0 (*Performance Monitors Event Type Register 0*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmevtyper0(pub u32);
+pub struct Pmevtyper0 {
+    /// Privileged filtering - when set, do not count events at EL1
+    #[bit(31, rw)]
+    p: bool,
+    /// User filtering - when set, do not count events at EL0
+    #[bit(30, rw)]
+    u: bool,
+    /// Non-secure EL1 filtering
+    #[bit(29, rw)]
+    nsk: bool,
+    /// Non-secure EL0 filtering
+    #[bit(28, rw)]
+    nsu: bool,
+    /// Non-secure EL2 filtering - when set, count events at EL2
+    #[bit(27, rw)]
+    nsh: bool,
+    /// The number of the event to count
+    #[bits(0..=15, rw)]
+    evt_count: u16,
+}
 
 impl SysReg for Pmevtyper0 {
     const CP: u32 = 15;
@@ -22,7 +40,7 @@ impl Pmevtyper0 {
     #[inline]
     /// Reads PMEVTYPER0 (*Performance Monitors Event Type Register 0*)
     pub fn read() -> Pmevtyper0 {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +55,7 @@ impl Pmevtyper0 {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
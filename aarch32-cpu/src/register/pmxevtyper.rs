@@ -3,10 +3,31 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMXEVTYPER (*Performance Monitors Selected Event Type Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmxevtyper(pub u32);
+pub struct Pmxevtyper {
+    /// Privileged filtering - when set, do not count events at EL1
+    #[bit(31, rw)]
+    p: bool,
+    /// User filtering - when set, do not count events at EL0
+    #[bit(30, rw)]
+    u: bool,
+    /// Non-secure EL1 filtering
+    #[bit(29, rw)]
+    nsk: bool,
+    /// Non-secure EL0 filtering
+    #[bit(28, rw)]
+    nsu: bool,
+    /// Non-secure EL2 filtering - when set, count events at EL2
+    #[bit(27, rw)]
+    nsh: bool,
+    /// The number of the event to count
+    ///
+    /// Set PMSELR to 31 to access PMCCFILTR through this register, in
+    /// which case this field is ignored.
+    #[bits(0..=15, rw)]
+    evt_count: u16,
+}
 
 impl SysReg for Pmxevtyper {
     const CP: u32 = 15;
@@ -22,7 +43,7 @@ impl Pmxevtyper {
     #[inline]
     /// Reads PMXEVTYPER (*Performance Monitors Selected Event Type Register*)
     pub fn read() -> Pmxevtyper {
-        unsafe { Self(<Self as SysRegRead>::read_raw()) }
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
     }
 }
 
@@ -37,7 +58,7 @@ impl Pmxevtyper {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
@@ -0,0 +1,79 @@
+//! Code for managing PRRR (*Primary Region Remap Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// The memory type a TEX remap region is mapped to
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemapMemoryType {
+    StronglyOrdered = 0b00,
+    Device = 0b01,
+    Normal = 0b10,
+    _Reserved = 0b11,
+}
+
+/// PRRR (*Primary Region Remap Register*)
+///
+/// Only used when the TEX remap is enabled with `SCTLR.TRE`. This register
+/// shares its encoding with MAIR0, which is used instead when the
+/// Long-descriptor translation table format is in use.
+#[bitbybit::bitfield(u32, default = 0, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prrr {
+    /// Not Outer Shareable, for each of the eight regions
+    ///
+    /// Only applies to shareable Normal memory.
+    #[bit(24, rw)]
+    nos: [bool; 8],
+    /// Shareable Normal memory attribute for S = 1
+    #[bit(19, rw)]
+    ns1: bool,
+    /// Shareable Normal memory attribute for S = 0
+    #[bit(18, rw)]
+    ns0: bool,
+    /// Shareable Device memory attribute for S = 1
+    #[bit(17, rw)]
+    ds1: bool,
+    /// Shareable Device memory attribute for S = 0
+    #[bit(16, rw)]
+    ds0: bool,
+    /// Memory type, for each of the eight regions
+    #[bits(0..=1, rw)]
+    tr: [RemapMemoryType; 8],
+}
+
+impl SysReg for Prrr {
+    const CP: u32 = 15;
+    const CRN: u32 = 10;
+    const OP1: u32 = 0;
+    const CRM: u32 = 2;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegRead for Prrr {}
+
+impl Prrr {
+    #[inline]
+    /// Reads PRRR (*Primary Region Remap Register*)
+    pub fn read() -> Prrr {
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
+    }
+}
+
+impl crate::register::SysRegWrite for Prrr {}
+
+impl Prrr {
+    #[inline]
+    /// Writes PRRR (*Primary Region Remap Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -12,6 +12,12 @@ pub struct Sctlr {
     /// The bitmask for the Thumb Exception Enable bit
     #[bits(30..=30, rw)]
     te: bool,
+    /// The bitmask for the Access Flag Enable bit
+    #[bits(29..=29, rw)]
+    afe: bool,
+    /// The bitmask for the TEX Remap Enable bit
+    #[bits(28..=28, rw)]
+    tre: bool,
     /// The bitmask for the Non-Maskable FIQ bit
     #[bits(27..=27, rw)]
     nmfi: bool,
@@ -106,9 +112,11 @@ impl core::fmt::Debug for Sctlr {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "SCTLR {{ IE={} TE={} NMFI={} EE={} U={} FI={} DZ={} BR={} RR={} V={} I={} Z={} SW={} C={} A={} M={} }}",
+            "SCTLR {{ IE={} TE={} AFE={} TRE={} NMFI={} EE={} U={} FI={} DZ={} BR={} RR={} V={} I={} Z={} SW={} C={} A={} M={} }}",
             self.ie() as u8,
             self.te() as u8,
+            self.afe() as u8,
+            self.tre() as u8,
             self.nmfi() as u8,
             self.ee() as u8,
             self.u() as u8,
@@ -130,6 +138,6 @@ impl core::fmt::Debug for Sctlr {
 #[cfg(feature = "defmt")]
 impl defmt::Format for Sctlr {
     fn format(&self, f: defmt::Formatter) {
-        defmt::write!(f, "SCTLR {{ IE={0=31..32} TE={0=30..31} NMFI={0=27..28} EE={0=25..26} U={0=22..23} FI={0=21..22} DZ={0=18..19} BR={0=17..18} RR={0=14..15} V={0=13..14} I={0=12..13} Z={0=11..12} SW={0=10..11} C={0=2..3} A={0=1..2} M={0=0..1} }}", self.raw_value())
+        defmt::write!(f, "SCTLR {{ IE={0=31..32} TE={0=30..31} AFE={0=29..30} TRE={0=28..29} NMFI={0=27..28} EE={0=25..26} U={0=22..23} FI={0=21..22} DZ={0=18..19} BR={0=17..18} RR={0=14..15} V={0=13..14} I={0=12..13} Z={0=11..12} SW={0=10..11} C={0=2..3} A={0=1..2} M={0=0..1} }}", self.raw_value())
     }
 }
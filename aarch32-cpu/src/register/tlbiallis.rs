@@ -0,0 +1,26 @@
+//! TLBIALLIS (*TLB Invalidate All, Inner Shareable Register*)
+
+use crate::register::SysReg;
+
+/// TLBIALLIS (*TLB Invalidate All, Inner Shareable Register*)
+///
+/// Like [`TlbIAll`](crate::register::TlbIAll), but for every processor in the
+/// Inner Shareable domain. Needs the Multiprocessing Extensions.
+pub struct TlbIAllIs;
+
+impl SysReg for TlbIAllIs {
+    const CP: u32 = 15;
+    const CRN: u32 = 8;
+    const OP1: u32 = 0;
+    const CRM: u32 = 3;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegWrite for TlbIAllIs {}
+
+impl TlbIAllIs {
+    #[inline]
+    pub fn write() {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(0) }
+    }
+}
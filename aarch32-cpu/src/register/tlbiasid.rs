@@ -0,0 +1,25 @@
+//! TLBIASID (*TLB Invalidate by ASID Register*)
+
+use crate::register::SysReg;
+
+/// TLBIASID (*TLB Invalidate by ASID Register*)
+///
+/// Invalidates every non-global TLB entry tagged with the given ASID.
+pub struct TlbIAsid;
+
+impl SysReg for TlbIAsid {
+    const CP: u32 = 15;
+    const CRN: u32 = 8;
+    const OP1: u32 = 0;
+    const CRM: u32 = 7;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegWrite for TlbIAsid {}
+
+impl TlbIAsid {
+    #[inline]
+    pub fn write(asid: u8) {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(asid as u32) }
+    }
+}
@@ -0,0 +1,128 @@
+//! Code for managing TTBCR (*Translation Table Base Control Register*)
+
+use arbitrary_int::u3;
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use super::ttbr0::Region;
+
+/// TTBCR (*Translation Table Base Control Register*)
+///
+/// Most of the fields here only apply when the Long-descriptor translation
+/// table format is selected with [`Ttbcr::eae`]. The Short-descriptor format
+/// only uses [`Ttbcr::n`], [`Ttbcr::pd0`] and [`Ttbcr::pd1`].
+#[bitbybit::bitfield(u32, default = 0, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ttbcr {
+    /// Extended Address Enable
+    ///
+    /// * eae = false => Short-descriptor translation table format
+    /// * eae = true => Long-descriptor translation table format
+    #[bit(31, rw)]
+    eae: bool,
+    /// Shareability attributes for TTBR1 table walks
+    #[bits(28..=29, rw)]
+    sh1: Shareability,
+    /// Outer cacheability attributes for TTBR1 table walks
+    #[bits(26..=27, rw)]
+    orgn1: Region,
+    /// Inner cacheability attributes for TTBR1 table walks
+    #[bits(24..=25, rw)]
+    irgn1: Region,
+    /// Translation walk Disable for TTBR1
+    #[bit(23, rw)]
+    epd1: bool,
+    /// Selects whether TTBR0 (false) or TTBR1 (true) defines the ASID
+    #[bit(22, rw)]
+    a1: bool,
+    /// The size offset of the memory region addressed by TTBR1
+    #[bits(16..=18, rw)]
+    t1sz: u3,
+    /// Shareability attributes for TTBR0 table walks
+    #[bits(12..=13, rw)]
+    sh0: Shareability,
+    /// Outer cacheability attributes for TTBR0 table walks
+    #[bits(10..=11, rw)]
+    orgn0: Region,
+    /// Inner cacheability attributes for TTBR0 table walks
+    #[bits(8..=9, rw)]
+    irgn0: Region,
+    /// Translation walk Disable for TTBR0
+    #[bit(7, rw)]
+    epd0: bool,
+    /// Translation table walk disable for TTBR1 (Short-descriptor format)
+    #[bit(5, rw)]
+    pd1: bool,
+    /// Translation table walk disable for TTBR0 (Short-descriptor format)
+    #[bit(4, rw)]
+    pd0: bool,
+    /// The size offset of the memory region addressed by TTBR0
+    #[bits(0..=2, rw)]
+    t0sz: u3,
+    /// Width of the base address held in TTBR0 (Short-descriptor format)
+    ///
+    /// When `N` is non-zero, TTBR0 translates the bottom `4 GiB >> N` of the
+    /// address space, using a table of `4096 >> N` entries, and TTBR1
+    /// translates everything above that.
+    #[bits(0..=2, rw)]
+    n: u3,
+}
+
+impl Ttbcr {
+    /// The first virtual address translated by TTBR1 (Short-descriptor format)
+    ///
+    /// Returns `None` if `N` is zero, when TTBR1 is not used.
+    pub const fn ttbr1_boundary(&self) -> Option<u32> {
+        match self.n().value() {
+            0 => None,
+            n => Some(1 << (32 - n)),
+        }
+    }
+}
+
+/// Shareability attributes, as used by the Long-descriptor format
+#[derive(Debug, PartialEq, Eq)]
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Shareability {
+    /// Non-shareable
+    NonShareable = 0b00,
+    _Reserved = 0b01,
+    /// Outer Shareable
+    OuterShareable = 0b10,
+    /// Inner Shareable
+    InnerShareable = 0b11,
+}
+
+impl SysReg for Ttbcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 2;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegRead for Ttbcr {}
+
+impl crate::register::SysRegWrite for Ttbcr {}
+
+impl Ttbcr {
+    #[inline]
+    /// Reads TTBCR (*Translation Table Base Control Register*)
+    pub fn read() -> Ttbcr {
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead>::read_raw()) }
+    }
+
+    #[inline]
+    /// Writes TTBCR (*Translation Table Base Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Code for managing the 64-bit form of TTBR0 (*Translation Table Base Register 0*)
+
+use arbitrary_int::u40;
+
+use crate::register::{SysReg64, SysRegRead64, SysRegWrite64};
+
+/// TTBR0 (*Translation Table Base Register 0*), as used with the Long-descriptor format
+#[bitbybit::bitfield(u64, default = 0, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ttbr0Lpae {
+    /// Address Space Identifier
+    #[bits(48..=55, rw)]
+    asid: u8,
+    /// Translation table base address
+    ///
+    /// The low bits must be zero, depending on the value of TTBCR.T0SZ.
+    #[bits(0..=39, rw)]
+    baddr: u40,
+}
+
+impl Ttbr0Lpae {
+    /// Get the translation table base address
+    pub fn get_address(&self) -> u64 {
+        self.baddr().value()
+    }
+
+    /// Change the translation table base address
+    pub fn with_address(self, address: u64) -> Self {
+        self.with_baddr(u40::new(address))
+    }
+}
+
+impl SysReg64 for Ttbr0Lpae {
+    const CP: u32 = 15;
+    const OP1: u32 = 0;
+    const CRM: u32 = 2;
+}
+
+impl SysRegRead64 for Ttbr0Lpae {}
+
+impl SysRegWrite64 for Ttbr0Lpae {}
+
+impl Ttbr0Lpae {
+    #[inline]
+    /// Reads the 64-bit form of TTBR0 (*Translation Table Base Register 0*)
+    pub fn read() -> Ttbr0Lpae {
+        unsafe { Self::new_with_raw_value(<Self as SysRegRead64>::read_raw()) }
+    }
+
+    #[inline]
+    /// Writes the 64-bit form of TTBR0 (*Translation Table Base Register 0*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite64>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -29,7 +29,7 @@ fn chip_info() {
 #[cfg(arm_architecture = "v7-r")]
 fn mpu_pmsa_v7() {
     use aarch32_cpu::{
-        pmsav7::{CacheablePolicy, Config, MemAttr, Mpu, Region, RegionSize},
+        pmsav7::{AccessPerms, CacheablePolicy, Config, MemAttr, Mpu, Region, RegionSize},
         register::Mpuir,
     };
 
@@ -61,6 +61,7 @@ fn mpu_pmsa_v7() {
             subregion_mask: 0x00,
             enabled: true,
             no_exec: false,
+            access: AccessPerms::FullAccess,
             mem_attr: MemAttr::Cacheable {
                 inner: CacheablePolicy::WriteThroughNoWriteAllocate,
                 outer: CacheablePolicy::NonCacheable,
@@ -42,9 +42,6 @@ pub fn want_panic() {
 ///
 /// Includes enabling the MMU (if we have one)
 pub fn init() {
-    #[cfg(arm_architecture = "v7-a")]
-    mmu::set_mmu();
-
     #[cfg(arm_architecture = "v7-a")]
     mmu::enable_mmu_and_cache();
 
@@ -19,7 +19,7 @@
 
 use aarch32_cpu::mmu::{
     AccessPermissions, CacheableMemoryAttribute, L1Section, L1Table, MemoryRegionAttributes,
-    NUM_L1_PAGE_TABLE_ENTRIES, SectionAttributes,
+    MmuConfig, NUM_L1_PAGE_TABLE_ENTRIES, SectionAttributes,
 };
 use arbitrary_int::u4;
 
@@ -78,29 +78,22 @@ const fn make_mmu_table() -> L1Table {
     }
 }
 
-/// Set the MMU base register to `MMU_L1_PAGE_TABLE`
-pub fn set_mmu() {
-    let ttbr0 = aarch32_cpu::register::Ttbr0::new_with_raw_value(0)
-        .with_address(core::ptr::addr_of!(MMU_L1_PAGE_TABLE) as usize)
-        .with_irgn(false)
-        .with_nos(false)
-        .with_rgn(aarch32_cpu::register::ttbr0::Region::WriteBackWriteAllocateCacheable)
-        .with_s(true)
-        .with_c(true);
-    unsafe { aarch32_cpu::register::Ttbr0::write(ttbr0) }
-}
-
-/// Enable the MMU and the cache
+/// Enable the MMU and the cache, using `MMU_L1_PAGE_TABLE`
 pub fn enable_mmu_and_cache() {
-    // Enable Manager access to Domain 0
-    aarch32_cpu::register::Dacr::modify(|d| {
-        d.set_d(0, aarch32_cpu::register::dacr::DomainAccess::Manager);
-    });
-    // This function contains the barrier we need to flush the pipeline
-    aarch32_cpu::register::Sctlr::modify(|s| {
-        // Enable Cache
-        s.set_c(true);
-        // Enable MMU
-        s.set_m(true);
-    });
+    let config = MmuConfig {
+        ttbr0: aarch32_cpu::register::Ttbr0::new_with_raw_value(0)
+            .with_irgn(false)
+            .with_nos(false)
+            .with_rgn(aarch32_cpu::register::ttbr0::Region::WriteBackWriteAllocateCacheable)
+            .with_s(true)
+            .with_c(true),
+        // Enable Manager access to Domain 0
+        dacr: aarch32_cpu::register::Dacr::new_with_raw_value(0)
+            .with_d(0, aarch32_cpu::register::dacr::DomainAccess::Manager),
+        instruction_cache: false,
+        branch_prediction: false,
+        ..MmuConfig::DEFAULT
+    };
+    // Safety: the table identity maps all of RAM and the peripherals
+    unsafe { aarch32_cpu::mmu::enable(&MMU_L1_PAGE_TABLE, None, config) }
 }